@@ -1,5 +1,7 @@
 use reqx_core::ast::HttpMethod;
 use reqx_core::client::{HttpClient, HttpResponse};
+use reqx_core::interpreter::ExecuteOptions;
+use reqx_core::reporter::SilentReporter;
 use reqx_core::{interpreter, lexer, parser};
 use std::fs;
 use std::process;
@@ -14,7 +16,7 @@ impl HttpClient for ExampleMockClient {
         method: &HttpMethod,
         url: &str,
         _headers: &[(String, String)],
-        _body: Option<&str>,
+        _body: Option<&[u8]>,
     ) -> Result<HttpResponse, String> {
         println!(">>> [MOCK] Intercepted a {} request to '{}'", method, url);
 
@@ -24,7 +26,7 @@ impl HttpClient for ExampleMockClient {
             status_is_client_error: false,
             status_is_server_error: false,
             headers: vec![("Content-Type".to_string(), "application/json".to_string())],
-            body: r#"{"message": "Hello from ExampleMockClient!"}"#.to_string(),
+            body: r#"{"message": "Hello from ExampleMockClient!"}"#.into(),
         })
     }
 }
@@ -47,22 +49,37 @@ fn main() {
     // We instantiate our custom mock client instead of reqwest
     let client = ExampleMockClient;
 
-    // Set arguments as if we called from CLI
-    let verbose = true;
-    let dry_run = false;
-    let request_index = None;
-    let method_filter = None;
+    // Set options as if we called from CLI
+    let options = ExecuteOptions {
+        dry_run: false,
+        request_index: None,
+        method_filter: None,
+        ..Default::default()
+    };
 
     println!("Executing parsed reqx file with Mock Client...\n");
-    if let Err(e) = interpreter::execute(
-        &client,
-        &reqx_file,
-        verbose,
-        dry_run,
-        request_index,
-        method_filter,
-    ) {
-        eprintln!("Execution error: {}", e);
-        process::exit(1);
+    let report = match interpreter::execute(&client, &reqx_file, &mut SilentReporter, &options) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Execution error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // `SilentReporter` discards progress events: inspect the final report instead.
+    for req in &report.requests {
+        let status = req
+            .response
+            .as_ref()
+            .map(|r| r.status.to_string())
+            .unwrap_or_else(|| "not sent".to_string());
+        println!(
+            "[{}/{}] {} {} -> {}",
+            req.index + 1,
+            report.total,
+            req.request.method,
+            req.request.url,
+            status
+        );
     }
 }
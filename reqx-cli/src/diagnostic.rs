@@ -0,0 +1,150 @@
+//! Compiler-style rendering of `ReqxError`s: the offending source line,
+//! a caret underline with a label, and a `help:` hint.
+
+use colored::Colorize;
+use reqx_core::error::ReqxError;
+
+/// Print `error` to stderr, quoting `source` (the contents of the `.reqx` file).
+pub fn print(error: &ReqxError, source: &str) {
+    eprintln!("{}", render(error, source));
+}
+
+/// The short text shown next to the carets.
+fn label(error: &ReqxError) -> String {
+    match error {
+        ReqxError::Lex { .. } => "this line cannot be tokenized".to_string(),
+        ReqxError::Parse { .. } => "unexpected input".to_string(),
+        ReqxError::UndefinedVariable { name, .. } => format!("`{}` is not defined", name),
+        ReqxError::Reference { .. } => "cannot be resolved".to_string(),
+        ReqxError::Builtin { .. } => "cannot be evaluated".to_string(),
+        ReqxError::Filter { .. } => "cannot be applied".to_string(),
+        ReqxError::File { .. } => "cannot be accessed".to_string(),
+        ReqxError::Extraction { .. } => "nothing to extract".to_string(),
+        ReqxError::UnclosedInterpolation { .. } => "this `{{` is never closed".to_string(),
+        ReqxError::Transport { .. } => "while sending this request".to_string(),
+        ReqxError::InvalidSelection { .. } => String::new(),
+    }
+}
+
+pub fn render(error: &ReqxError, source: &str) -> String {
+    let mut out = format!("{}: {}", "error".red().bold(), error.message().bold());
+
+    if let Some(location) = error.location() {
+        let span = location.span;
+        let line_text = source
+            .lines()
+            .nth(span.line.saturating_sub(1))
+            .unwrap_or("");
+        let gutter = " ".repeat(span.line.to_string().len());
+
+        let path = location
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<input>".to_string());
+        out.push_str(&format!(
+            "\n{}{} {}:{}:{}",
+            gutter,
+            "-->".blue().bold(),
+            path,
+            span.line,
+            span.column
+        ));
+        out.push_str(&format!("\n{} {}", gutter, "|".blue().bold()));
+        out.push_str(&format!(
+            "\n{} {} {}",
+            span.line.to_string().blue().bold(),
+            "|".blue().bold(),
+            line_text
+        ));
+
+        // Underline the span, clipped to the end of its first line.
+        let prefix: String = line_text
+            .chars()
+            .take(span.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let highlighted = source
+            .get(span.start..span.end)
+            .and_then(|text| text.lines().next())
+            .map(|text| text.chars().count())
+            .unwrap_or(0)
+            .max(1);
+        out.push_str(&format!(
+            "\n{} {} {}{} {}",
+            gutter,
+            "|".blue().bold(),
+            prefix,
+            "^".repeat(highlighted).red().bold(),
+            label(error).red().bold()
+        ));
+
+        if let Some(help) = error.help() {
+            out.push_str(&format!("\n{} {}", gutter, "|".blue().bold()));
+            out.push_str(&format!(
+                "\n{} {} {}",
+                gutter,
+                "=".blue().bold(),
+                help_line(&help)
+            ));
+        }
+    } else if let Some(help) = error.help() {
+        out.push_str(&format!("\n  {} {}", "=".blue().bold(), help_line(&help)));
+    }
+
+    out
+}
+
+fn help_line(help: &str) -> String {
+    format!("{} {}", "help:".bold(), help)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqx_core::{interpreter, lexer, parser};
+
+    #[test]
+    fn test_render_undefined_variable() {
+        colored::control::set_override(false);
+
+        let source = "@base_url = https://api.example.com\n\nGET {{base_ur}}/users\n";
+        let file = parser::parse(lexer::tokenize(source).unwrap()).unwrap();
+        let err = interpreter::execute(
+            &NoClient,
+            &file,
+            &mut reqx_core::reporter::SilentReporter,
+            &interpreter::ExecuteOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err()
+        .with_path("api.reqx");
+
+        assert_eq!(
+            render(&err, source),
+            "error: Undefined variable: base_ur
+ --> api.reqx:3:5
+  |
+3 | GET {{base_ur}}/users
+  |     ^^^^^^^^^^^ `base_ur` is not defined
+  |
+  = help: did you mean `{{base_url}}`?"
+        );
+    }
+
+    struct NoClient;
+
+    impl reqx_core::client::HttpClient for NoClient {
+        fn execute(
+            &self,
+            _method: &reqx_core::ast::HttpMethod,
+            _url: &str,
+            _headers: &[(String, String)],
+            _body: Option<&[u8]>,
+        ) -> Result<reqx_core::client::HttpResponse, String> {
+            Err("not connected".to_string())
+        }
+    }
+}
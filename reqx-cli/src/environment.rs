@@ -0,0 +1,107 @@
+//! Loading variables from outside the `.reqx` file: named environments from
+//! `reqx.env.json`, `--var` and `--vars-file` overrides, and the values
+//! `{{$env.NAME}}` falls back to when the process environment does not set
+//! `NAME`.
+
+use reqx_core::environment::{self, Environments};
+use reqx_core::interpreter::VariableLayer;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ENVIRONMENTS_FILE: &str = "reqx.env.json";
+
+/// The variable layers of environment `env` (or only the shared variables
+/// without one), from the `reqx.env.json` closest to the `.reqx` file: in its
+/// directory or the nearest parent directory that has one.
+pub fn environment(reqx_path: &str, env: Option<&str>) -> Result<Vec<VariableLayer>, String> {
+    let Some(path) = find_environments(reqx_path) else {
+        return match env {
+            Some(_) => Err(format!(
+                "No {} found next to '{}' or in its parent directories",
+                ENVIRONMENTS_FILE, reqx_path
+            )),
+            None => Ok(Vec::new()),
+        };
+    };
+    let error = |e: String| format!("Error reading '{}': {}", path.display(), e);
+    let contents = fs::read_to_string(&path).map_err(|e| error(e.to_string()))?;
+    Environments::parse(&contents).map_err(error)?.select(env)
+}
+
+/// The variables given with `--vars-file` and `--var`, in increasing order of
+/// precedence.
+pub fn overrides(
+    vars_file: Option<&str>,
+    vars: &[(String, String)],
+) -> Result<Vec<VariableLayer>, String> {
+    let mut layers = Vec::new();
+    if let Some(path) = vars_file {
+        let error = |e: String| format!("Error reading vars file '{}': {}", path, e);
+        let contents = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        layers.push(VariableLayer {
+            name: path.to_string(),
+            values: environment::parse_variables(&contents).map_err(error)?,
+            untyped: false,
+        });
+    }
+    if !vars.is_empty() {
+        layers.push(VariableLayer {
+            name: "--var".to_string(),
+            values: vars
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone().into()))
+                .collect(),
+            untyped: true,
+        });
+    }
+    Ok(layers)
+}
+
+/// Parse a `--var name=value` argument.
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected `name=value`, got `{}`", arg)),
+    }
+}
+
+fn find_environments(reqx_path: &str) -> Option<PathBuf> {
+    let dir = Path::new(reqx_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = fs::canonicalize(dir).ok()?;
+    dir.ancestors()
+        .map(|d| d.join(ENVIRONMENTS_FILE))
+        .find(|p| p.is_file())
+}
+
+/// The `.env` values for the `.reqx` file at `reqx_path`: from `env_file` if
+/// one was given, otherwise from a `.env` file next to the `.reqx` file, if
+/// there is one.
+pub fn dotenv(reqx_path: &str, env_file: Option<&str>) -> Result<HashMap<String, String>, String> {
+    let path = match env_file {
+        Some(path) => Path::new(path).to_path_buf(),
+        None => {
+            let path = Path::new(reqx_path)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(".env");
+            if !path.is_file() {
+                return Ok(HashMap::new());
+            }
+            path
+        }
+    };
+
+    let entries = dotenvy::from_path_iter(&path)
+        .map_err(|e| format!("Error reading env file '{}': {}", path.display(), e))?;
+    entries
+        .map(|entry| {
+            entry.map_err(|e| format!("Error reading env file '{}': {}", path.display(), e))
+        })
+        .collect()
+}
@@ -1,20 +1,53 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
 
-use reqx_core::interpreter;
+use reqx_core::interpreter::{self, ExecuteOptions};
 use reqx_core::lexer;
 use reqx_core::parser;
 
+mod diagnostic;
+mod environment;
+mod pretty;
+mod render;
 mod reqwest_client;
+mod test_report;
+mod test_runner;
 
 /// reqx — Execute HTTP requests defined in .reqx files
 #[derive(Parser, Debug)]
-#[command(name = "reqx", version, about = "A DSL interpreter for HTTP requests")]
+#[command(
+    name = "reqx",
+    version,
+    about = "A DSL interpreter for HTTP requests",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run .reqx files as tests and write JUnit XML / TAP reports
+    ///
+    /// Every request is a test case. A request that fails with an error, such
+    /// as a transport failure or a missing extraction, is reported as an error
+    /// and the following requests still run.
+    Test(test_runner::TestArgs),
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
     /// Path to the .reqx file to execute
-    file: String,
+    #[arg(required = true)]
+    file: Option<String>,
 
     /// Show verbose output (headers, body details)
     #[arg(short, long)]
@@ -31,19 +64,63 @@ struct Cli {
     /// Execute only requests that match this HTTP method (e.g., GET, POST)
     #[arg(short = 'm', long = "method")]
     method_filter: Option<String>,
+
+    /// Execute only requests whose `# @name` matches (glob patterns like `user-*` allowed; repeatable)
+    #[arg(short = 'n', long = "name", value_name = "NAME")]
+    names: Vec<String>,
+
+    /// Run the valid requests even if some requests failed to parse
+    #[arg(long)]
+    keep_going: bool,
+
+    /// Seed the random values of `{{$uuid}}`, `{{$randomInt}}` and `{{$randomString}}`
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Read `{{$env.NAME}}` fallbacks from this file instead of the `.env` next to the .reqx file
+    #[arg(long, value_name = "PATH")]
+    env_file: Option<String>,
+
+    /// Use the variables of this environment from reqx.env.json
+    #[arg(short = 'e', long = "env", value_name = "NAME")]
+    environment: Option<String>,
+
+    /// Set a variable, overriding the file and the environment (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = environment::parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Set variables from a JSON object, overriding the file and the environment
+    #[arg(long, value_name = "PATH")]
+    vars_file: Option<String>,
+
+    /// Save every response body to this directory, named after its request
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// With --output-dir, also save the status and headers of each response
+    #[arg(long, requires = "output_dir")]
+    output_headers: bool,
 }
 
 fn main() {
     let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Test(args)) => process::exit(test_runner::run(args)),
+        None => run(cli.run),
+    }
+}
+
+fn run(cli: RunArgs) {
+    let path = cli.file.unwrap_or_default();
 
     // Read the input file
-    let contents = match fs::read_to_string(&cli.file) {
+    let contents = match fs::read_to_string(&path) {
         Ok(c) => c,
         Err(e) => {
             eprintln!(
                 "{} Error reading file '{}': {}",
                 "✖".red().bold(),
-                cli.file.bold(),
+                path.bold(),
                 e
             );
             process::exit(1);
@@ -54,31 +131,79 @@ fn main() {
     let tokens = match lexer::tokenize(&contents) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("{} Lexer error: {}", "✖".red().bold(), e);
+            diagnostic::print(&e.with_path(&path), &contents);
             process::exit(1);
         }
     };
 
-    // Parse
-    let reqx_file = match parser::parse(tokens) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("{} Parser error: {}", "✖".red().bold(), e);
+    // Parse, reporting every broken request at once
+    let output = parser::parse_recovering(tokens);
+    for e in &output.errors {
+        diagnostic::print(&e.clone().with_path(&path), &contents);
+        eprintln!();
+    }
+    if !output.errors.is_empty() && !cli.keep_going {
+        eprintln!(
+            "{} aborting due to {} parse error(s); use --keep-going to run the valid requests",
+            "✖".red().bold(),
+            output.errors.len()
+        );
+        process::exit(1);
+    }
+    let reqx_file = output.file;
+
+    let loaded = environment::dotenv(&path, cli.env_file.as_deref()).and_then(|env| {
+        Ok((
+            env,
+            environment::environment(&path, cli.environment.as_deref())?,
+            environment::overrides(cli.vars_file.as_deref(), &cli.vars)?,
+        ))
+    });
+    let (env, layers, overrides) = match loaded {
+        Ok(loaded) => loaded,
+        Err(message) => {
+            eprintln!("{} {}", "✖".red().bold(), message);
             process::exit(1);
         }
     };
 
     // Execute
     let client = reqwest_client::ReqwestClient::new();
-    if let Err(e) = interpreter::execute(
-        &client,
-        &reqx_file,
-        cli.verbose,
-        cli.dry_run,
-        cli.request_index,
-        cli.method_filter,
-    ) {
-        eprintln!("{} Execution error: {}", "✖".red().bold(), e);
+    let mut reporter = render::TerminalReporter::new(cli.verbose);
+    let options = ExecuteOptions {
+        dry_run: cli.dry_run,
+        request_index: cli.request_index,
+        method_filter: cli.method_filter.clone(),
+        name_patterns: cli.names,
+        seed: cli.seed,
+        env,
+        environment: layers,
+        overrides,
+        base_dir: Path::new(&path).parent().unwrap_or(Path::new("")).into(),
+        output_dir: cli.output_dir,
+        output_headers: cli.output_headers,
+        ..Default::default()
+    };
+    let report = match interpreter::execute(&client, &reqx_file, &mut reporter, &options) {
+        Ok(r) => r,
+        Err(e) => {
+            diagnostic::print(&e.with_path(&path), &contents);
+            process::exit(1);
+        }
+    };
+
+    if let Some(m_str) = cli.method_filter {
+        if report.requests.is_empty() {
+            println!(
+                "{}",
+                format!("No requests matched the method filter: {}", m_str).dimmed()
+            );
+        }
+    }
+
+    let failures = report.failures().count();
+    if failures > 0 {
+        eprintln!("{} {} assertion(s) failed", "✖".red().bold(), failures);
         process::exit(1);
     }
 }
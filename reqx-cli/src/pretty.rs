@@ -0,0 +1,284 @@
+//! Re-indenting JSON, XML and HTML bodies for display, and dumping binary ones.
+
+/// HTML elements that never have a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// HTML elements whose content is shown exactly as received.
+const RAW_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea"];
+
+/// `body` re-indented for display, or `None` if it is not JSON, XML or HTML
+/// (or is too broken to re-indent).
+pub fn body(body: &str, content_type: Option<&str>) -> Option<String> {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(body) {
+        return serde_json::to_string_pretty(&json).ok();
+    }
+    let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+    let start = body.trim_start();
+    let html = content_type.contains("html")
+        || start
+            .get(..14)
+            .is_some_and(|s| s.eq_ignore_ascii_case("<!doctype html"));
+    if !(html || content_type.contains("xml") || start.starts_with('<')) {
+        return None;
+    }
+    markup(body, html)
+}
+
+/// The first `max` bytes of `bytes` as `xxd`-style lines: the offset, 16
+/// bytes in hex and their printable ASCII characters.
+pub fn hex_dump(bytes: &[u8], max: usize) -> Vec<String> {
+    bytes[..bytes.len().min(max)]
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| match b {
+                    0x20..=0x7e => b as char,
+                    _ => '.',
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece<'a> {
+    /// `<name ...>`
+    Open(&'a str, &'a str),
+    /// `</name>`
+    Close(&'a str, &'a str),
+    /// A tag that opens nothing: `<br>`, `<item/>`, `<?xml ...?>`, `<!-- -->`
+    Single(&'a str),
+    Text(&'a str),
+    /// The content of a `<pre>` or `<script>`, kept as it is.
+    Raw(&'a str),
+}
+
+/// Indent XML or HTML by two spaces per nesting level, keeping elements that
+/// only hold a line of text on one line.
+fn markup(body: &str, html: bool) -> Option<String> {
+    let pieces = split(body.trim(), html)?;
+    let mut out = String::new();
+    let mut open: Vec<&str> = Vec::new();
+    let line = |out: &mut String, depth: usize, text: &str| {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(text);
+        out.push('\n');
+    };
+
+    let mut i = 0;
+    while i < pieces.len() {
+        match pieces[i] {
+            Piece::Open(name, tag) => {
+                // `<id>7</id>` and `<p></p>` stay on one line.
+                match (pieces.get(i + 1), pieces.get(i + 2)) {
+                    (Some(Piece::Text(text)), Some(Piece::Close(close, end)))
+                        if close.eq_ignore_ascii_case(name) && !text.contains('\n') =>
+                    {
+                        line(
+                            &mut out,
+                            open.len(),
+                            &format!("{}{}{}", tag, text.trim(), end),
+                        );
+                        i += 3;
+                        continue;
+                    }
+                    (Some(Piece::Close(close, end)), _) if close.eq_ignore_ascii_case(name) => {
+                        line(&mut out, open.len(), &format!("{}{}", tag, end));
+                        i += 2;
+                        continue;
+                    }
+                    _ => {}
+                }
+                line(&mut out, open.len(), tag);
+                open.push(name);
+            }
+            Piece::Close(name, tag) => {
+                // Elements HTML lets you leave open (`<p>`, `<li>`) are closed
+                // along with their parent.
+                if let Some(pos) = open.iter().rposition(|n| n.eq_ignore_ascii_case(name)) {
+                    open.truncate(pos);
+                }
+                line(&mut out, open.len(), tag);
+            }
+            Piece::Single(tag) => line(&mut out, open.len(), tag),
+            Piece::Text(text) => {
+                for text in text.lines().map(str::trim).filter(|t| !t.is_empty()) {
+                    line(&mut out, open.len(), text);
+                }
+            }
+            Piece::Raw(text) => {
+                out.push_str(text.trim_matches('\n'));
+                out.push('\n');
+            }
+        }
+        i += 1;
+    }
+    out.pop();
+    Some(out)
+}
+
+/// Split markup into tags and the text between them.
+fn split(mut rest: &str, html: bool) -> Option<Vec<Piece<'_>>> {
+    let mut pieces = Vec::new();
+    while !rest.is_empty() {
+        if !starts_tag(rest) {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(i, _)| starts_tag(&rest[i..]))
+                .map_or(rest.len(), |(i, _)| i);
+            if !rest[..end].trim().is_empty() {
+                pieces.push(Piece::Text(&rest[..end]));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->")? + 3
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>")? + 3
+        } else {
+            tag_end(rest)?
+        };
+        let (tag, after) = rest.split_at(end);
+        rest = after;
+
+        if let Some(name) = tag.strip_prefix("</") {
+            pieces.push(Piece::Close(tag_name(name), tag));
+        } else if tag.starts_with("<!") || tag.starts_with("<?") || tag.ends_with("/>") {
+            pieces.push(Piece::Single(tag));
+        } else {
+            let name = tag_name(&tag[1..]);
+            let is = |list: &[&str]| html && list.iter().any(|n| n.eq_ignore_ascii_case(name));
+            if is(VOID_ELEMENTS) {
+                pieces.push(Piece::Single(tag));
+            } else if is(RAW_ELEMENTS) {
+                pieces.push(Piece::Open(name, tag));
+                let close = rest
+                    .to_ascii_lowercase()
+                    .find(&format!("</{}", name.to_ascii_lowercase()))?;
+                if !rest[..close].trim().is_empty() {
+                    pieces.push(Piece::Raw(&rest[..close]));
+                }
+                rest = &rest[close..];
+            } else {
+                pieces.push(Piece::Open(name, tag));
+            }
+        }
+    }
+    Some(pieces)
+}
+
+/// Whether `text` starts with a tag rather than a stray `<`, as in `a < b`.
+fn starts_tag(text: &str) -> bool {
+    text.strip_prefix('<').is_some_and(|rest| {
+        rest.starts_with(|c: char| c.is_ascii_alphabetic() || "/!?".contains(c))
+    })
+}
+
+/// The length of the tag at the start of `text`, up to and including its
+/// `>`, skipping over quoted attribute values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tag_name(text: &str) -> &str {
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump() {
+        let bytes: Vec<u8> = (0x40..0x62).collect();
+        let lines = hex_dump(&bytes, 20);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000000  40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f  @ABCDEFGHIJKLMNO"
+        );
+        assert_eq!(
+            lines[1],
+            "00000010  50 51 52 53                                      PQRS"
+        );
+        assert_eq!(
+            hex_dump(&[0x89, b'P'], 64)[0],
+            format!("00000000  89 50{}  .P", " ".repeat(42))
+        );
+    }
+
+    #[test]
+    fn test_pretty_xml() {
+        let xml = r#"<?xml version="1.0"?><users><user id="7"><name>Ada</name><tags/></user><empty></empty></users>"#;
+        assert_eq!(
+            body(xml, Some("application/xml")).unwrap(),
+            r#"<?xml version="1.0"?>
+<users>
+  <user id="7">
+    <name>Ada</name>
+    <tags/>
+  </user>
+  <empty></empty>
+</users>"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_html() {
+        let page = "<!DOCTYPE html><html><body><p>one<p>two<br><a href=\"/x?a>b\">x</a></body>\
+                    <script>if (a < b) {\n  go();\n}</script></html>";
+        assert_eq!(
+            body(page, Some("text/html; charset=utf-8")).unwrap(),
+            r#"<!DOCTYPE html>
+<html>
+  <body>
+    <p>
+      one
+      <p>
+        two
+        <br>
+        <a href="/x?a>b">x</a>
+  </body>
+  <script>
+if (a < b) {
+  go();
+}
+  </script>
+</html>"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_other_bodies() {
+        assert_eq!(body(r#"{"a":1}"#, None).unwrap(), "{\n  \"a\": 1\n}");
+        assert_eq!(body("plain text", Some("text/plain")), None);
+        assert_eq!(
+            body("<p>é < ü</p>", Some("text/html")).unwrap(),
+            "<p>é < ü</p>"
+        );
+        assert_eq!(body("<unclosed attr=\"", Some("text/xml")), None);
+    }
+}
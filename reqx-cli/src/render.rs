@@ -0,0 +1,239 @@
+//! Coloured terminal output, driven by interpreter events.
+
+use crate::pretty;
+use colored::Colorize;
+use reqx_core::ast::{HttpMethod, Request};
+use reqx_core::client::HttpResponse;
+use reqx_core::jsonpath::to_text;
+use reqx_core::report::{
+    AssertionResult, Extraction, RequestReport, ResolvedRequest, VariableOrigin,
+};
+use reqx_core::reporter::{Reporter, SkipReason};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// The default `reqx` output: one coloured block per request.
+pub struct TerminalReporter {
+    verbose: bool,
+    /// Whether the "Extractions:" heading was printed for the current request.
+    extractions_started: bool,
+}
+
+impl TerminalReporter {
+    pub fn new(verbose: bool) -> Self {
+        Self {
+            verbose,
+            extractions_started: false,
+        }
+    }
+}
+
+impl Reporter for TerminalReporter {
+    fn run_started(
+        &mut self,
+        variables: &HashMap<String, Value>,
+        origins: &HashMap<String, VariableOrigin>,
+    ) {
+        if !self.verbose {
+            return;
+        }
+        println!("{}", "── Variables ──".dimmed());
+        let mut names: Vec<&String> = variables.keys().collect();
+        names.sort();
+        for k in names {
+            let origin = match origins.get(k) {
+                Some(VariableOrigin::Environment(env)) => format!("(env {})", env),
+                Some(VariableOrigin::Override(source)) => format!("({})", source),
+                Some(VariableOrigin::File) | None => "(file)".to_string(),
+            };
+            println!(
+                "  {} = {} {}",
+                k.cyan(),
+                to_text(&variables[k]),
+                origin.dimmed()
+            );
+        }
+        println!();
+    }
+
+    fn request_started(&mut self, index: usize, total: usize, request: &Request) {
+        self.extractions_started = false;
+
+        let title = match request.name {
+            Some(ref name) => format!("━━━ Request {}/{} · {} ━━━", index + 1, total, name),
+            None => format!("━━━ Request {}/{} ━━━", index + 1, total),
+        };
+        println!("{}", title.bold().blue());
+
+        if let Some(ref comment) = request.comment {
+            println!("{} {}", "▸".green(), comment.bold());
+        }
+    }
+
+    fn request_resolved(&mut self, req: &ResolvedRequest) {
+        let method_colored = match req.method {
+            HttpMethod::Get => "GET".green().bold(),
+            HttpMethod::Post => "POST".yellow().bold(),
+            HttpMethod::Put => "PUT".blue().bold(),
+            HttpMethod::Patch => "PATCH".magenta().bold(),
+            HttpMethod::Delete => "DELETE".red().bold(),
+            HttpMethod::Head => "HEAD".cyan().bold(),
+            HttpMethod::Options => "OPTIONS".white().bold(),
+        };
+
+        println!("{} {}", method_colored, req.url.underline());
+
+        if !self.verbose {
+            return;
+        }
+        for (k, v) in &req.headers {
+            println!("  {}: {}", k.dimmed(), v);
+        }
+        if let Some(ref bytes) = req.body {
+            println!("  {}", "Body:".dimmed());
+            let Some(b) = req.body_text() else {
+                print_binary(bytes, true);
+                return;
+            };
+            let content_type = req
+                .headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                .map(|(_, v)| v.as_str());
+            let pretty = pretty::body(b, content_type);
+            for line in pretty.as_deref().unwrap_or(b).lines() {
+                println!("    {}", line);
+            }
+        }
+    }
+
+    fn response_received(&mut self, response: &HttpResponse, duration: Duration) {
+        let status = response.status;
+        let status_colored = if response.status_is_success {
+            format!("{}", status).green().bold()
+        } else if response.status_is_client_error {
+            format!("{}", status).yellow().bold()
+        } else if response.status_is_server_error {
+            format!("{}", status).red().bold()
+        } else {
+            format!("{}", status).white().bold()
+        };
+
+        println!(
+            "  {} {} {}",
+            "Status:".dimmed(),
+            status_colored,
+            format!("({} ms)", duration.as_millis()).dimmed()
+        );
+
+        if self.verbose {
+            println!("  {}", "Response Headers:".dimmed());
+            for (k, v) in &response.headers {
+                println!("    {}: {}", k.as_str().dimmed(), v.as_str());
+            }
+        }
+
+        if response.body.is_empty() {
+            return;
+        }
+
+        println!("  {}", "Response Body:".dimmed());
+        if response.is_binary() {
+            print_binary(&response.body, self.verbose);
+            return;
+        }
+
+        // Pretty-print JSON, XML and HTML
+        let resp_body = response.text();
+        if let Some(pretty) = pretty::body(&resp_body, response.header("content-type")) {
+            for line in pretty.lines() {
+                println!("    {}", line);
+            }
+        } else {
+            // Limit output for very large responses
+            let max_lines = 50;
+            let lines: Vec<&str> = resp_body.lines().collect();
+            for line in lines.iter().take(max_lines) {
+                println!("    {}", line);
+            }
+            if lines.len() > max_lines {
+                println!(
+                    "    {}",
+                    format!("... ({} more lines)", lines.len() - max_lines).dimmed()
+                );
+            }
+        }
+    }
+
+    fn extraction_performed(&mut self, extraction: &Extraction) {
+        if !self.verbose {
+            return;
+        }
+
+        if !self.extractions_started {
+            println!("  {}", "Extractions:".dimmed());
+            self.extractions_started = true;
+        }
+        match extraction.value {
+            Some(ref val) => println!("    {} = {}", extraction.name.cyan(), to_text(val)),
+            None => println!(
+                "    {} = {}",
+                extraction.name.cyan(),
+                format!("(not found: {})", extraction.source).yellow()
+            ),
+        }
+    }
+
+    fn assertion_evaluated(&mut self, result: &AssertionResult) {
+        if result.passed {
+            println!("  {} {}", "✔".green().bold(), result.text);
+        } else {
+            println!(
+                "  {} {} {}",
+                "✖".red().bold(),
+                result.text.red(),
+                format!("({})", result.message.as_deref().unwrap_or("failed")).dimmed()
+            );
+        }
+    }
+
+    fn response_saved(&mut self, path: &Path) {
+        println!("  {}", format!("Saved to {}", path.display()).dimmed());
+    }
+
+    fn request_skipped(&mut self, _index: usize, _request: &Request, reason: SkipReason) {
+        if reason == SkipReason::DryRun {
+            println!("{}", "  (dry-run: request not sent)".dimmed().italic());
+        }
+    }
+
+    fn request_finished(&mut self, _report: &RequestReport) {
+        println!();
+    }
+}
+
+/// How many bytes of a binary body `-v` shows.
+const HEX_DUMP_BYTES: usize = 256;
+
+/// A `(binary, N bytes)` summary of a body, followed by the start of its hex
+/// dump if `dump` is set.
+fn print_binary(bytes: &[u8], dump: bool) {
+    println!(
+        "    {}",
+        format!("(binary, {} bytes)", bytes.len()).dimmed()
+    );
+    if !dump {
+        return;
+    }
+    for line in pretty::hex_dump(bytes, HEX_DUMP_BYTES) {
+        println!("    {}", line);
+    }
+    if bytes.len() > HEX_DUMP_BYTES {
+        println!(
+            "    {}",
+            format!("... ({} more bytes)", bytes.len() - HEX_DUMP_BYTES).dimmed()
+        );
+    }
+}
@@ -26,7 +26,7 @@ impl HttpClient for ReqwestClient {
         method: &HttpMethod,
         url: &str,
         headers: &[(String, String)],
-        body: Option<&str>,
+        body: Option<&[u8]>,
     ) -> Result<HttpResponse, String> {
         let mut builder = match method {
             HttpMethod::Get => self.client.get(url),
@@ -43,7 +43,7 @@ impl HttpClient for ReqwestClient {
         }
 
         if let Some(b) = body {
-            builder = builder.body(b.to_string());
+            builder = builder.body(b.to_vec());
         }
 
         let response = builder
@@ -64,8 +64,8 @@ impl HttpClient for ReqwestClient {
             ));
         }
 
-        let body_text = response
-            .text()
+        let body = response
+            .bytes()
             .map_err(|e| format!("Failed to read response body: {}", e))?;
 
         Ok(HttpResponse {
@@ -74,7 +74,7 @@ impl HttpClient for ReqwestClient {
             status_is_client_error,
             status_is_server_error,
             headers: out_headers,
-            body: body_text,
+            body: body.to_vec(),
         })
     }
 }
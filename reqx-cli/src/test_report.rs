@@ -0,0 +1,241 @@
+//! Test results of `reqx test` and their JUnit XML / TAP serialisations.
+
+use std::time::Duration;
+
+/// The test cases of one `.reqx` file.
+pub struct TestSuite {
+    pub name: String,
+    pub cases: Vec<TestCase>,
+}
+
+/// One request, seen as a test.
+pub struct TestCase {
+    pub name: String,
+    pub duration: Duration,
+    pub outcome: Outcome,
+    /// The beginning of the response body, if there was one.
+    pub output: Option<String>,
+}
+
+pub enum Outcome {
+    Passed,
+    /// An assertion failed or the status was 4xx/5xx.
+    Failed(String),
+    /// The request could not be parsed, resolved or sent.
+    Error(String),
+    Skipped(String),
+}
+
+impl TestSuite {
+    fn count(&self, pred: impl Fn(&Outcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| pred(&c.outcome)).count()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Failed(_)))
+    }
+
+    pub fn errors(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Error(_)))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Skipped(_)))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.cases.iter().map(|c| c.duration).sum()
+    }
+}
+
+/// Serialise the suites as a JUnit XML document.
+pub fn junit(suites: &[TestSuite]) -> String {
+    let tests: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let failures: usize = suites.iter().map(TestSuite::failures).sum();
+    let errors: usize = suites.iter().map(TestSuite::errors).sum();
+    let skipped: usize = suites.iter().map(TestSuite::skipped).sum();
+    let time: Duration = suites.iter().map(TestSuite::duration).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"reqx\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        tests,
+        failures,
+        errors,
+        skipped,
+        time.as_secs_f64()
+    ));
+    for suite in suites {
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&suite.name),
+            suite.cases.len(),
+            suite.failures(),
+            suite.errors(),
+            suite.skipped(),
+            suite.duration().as_secs_f64()
+        ));
+        for case in &suite.cases {
+            out.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(&suite.name),
+                case.duration.as_secs_f64()
+            ));
+            if matches!(case.outcome, Outcome::Passed) && case.output.is_none() {
+                out.push_str("/>\n");
+                continue;
+            }
+            out.push_str(">\n");
+            match &case.outcome {
+                Outcome::Passed => {}
+                Outcome::Failed(message) => out.push_str(&format!(
+                    "      <failure message=\"{}\" type=\"AssertionFailure\">{}</failure>\n",
+                    xml_escape(first_line(message)),
+                    xml_escape(message)
+                )),
+                Outcome::Error(message) => out.push_str(&format!(
+                    "      <error message=\"{}\" type=\"Error\">{}</error>\n",
+                    xml_escape(first_line(message)),
+                    xml_escape(message)
+                )),
+                Outcome::Skipped(message) => out.push_str(&format!(
+                    "      <skipped message=\"{}\"/>\n",
+                    xml_escape(message)
+                )),
+            }
+            if let Some(ref output) = case.output {
+                out.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    xml_escape(output)
+                ));
+            }
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+/// Serialise the suites as a TAP version 13 stream.
+pub fn tap(suites: &[TestSuite]) -> String {
+    let total: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let mut out = format!("TAP version 13\n1..{}\n", total);
+
+    let mut n = 0;
+    for suite in suites {
+        for case in &suite.cases {
+            n += 1;
+            let description = format!("{} - {}", suite.name, case.name).replace('#', "\\#");
+            let (ok, directive, message) = match &case.outcome {
+                Outcome::Passed => ("ok", String::new(), None),
+                Outcome::Failed(m) | Outcome::Error(m) => ("not ok", String::new(), Some(m)),
+                Outcome::Skipped(m) => ("ok", format!(" # SKIP {}", m), None),
+            };
+            out.push_str(&format!("{} {} {}{}\n", ok, n, description, directive));
+
+            out.push_str("  ---\n");
+            if let Some(message) = message {
+                // A JSON string is a valid YAML scalar.
+                out.push_str(&format!(
+                    "  message: {}\n",
+                    serde_json::Value::String(message.clone())
+                ));
+                let severity = match case.outcome {
+                    Outcome::Error(_) => "error",
+                    _ => "fail",
+                };
+                out.push_str(&format!("  severity: {}\n", severity));
+            }
+            out.push_str(&format!("  duration_ms: {}\n", case.duration.as_millis()));
+            if let Some(ref output) = case.output {
+                out.push_str("  response: |\n");
+                for line in output.lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+            out.push_str("  ...\n");
+        }
+    }
+    out
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("")
+}
+
+/// Escape text for XML content and attribute values, dropping characters XML 1.0 forbids.
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' | '\r' | '\t' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suites() -> Vec<TestSuite> {
+        vec![TestSuite {
+            name: "api.reqx".to_string(),
+            cases: vec![
+                TestCase {
+                    name: "1. Login".to_string(),
+                    duration: Duration::from_millis(120),
+                    outcome: Outcome::Passed,
+                    output: None,
+                },
+                TestCase {
+                    name: "2. Create <user>".to_string(),
+                    duration: Duration::from_millis(80),
+                    outcome: Outcome::Failed("expected status == 201, got 400".to_string()),
+                    output: Some("{\"error\": \"bad\"}".to_string()),
+                },
+                TestCase {
+                    name: "3. Delete".to_string(),
+                    duration: Duration::ZERO,
+                    outcome: Outcome::Skipped("not run".to_string()),
+                    output: None,
+                },
+            ],
+        }]
+    }
+
+    #[test]
+    fn test_junit() {
+        let xml = junit(&suites());
+        assert!(xml.contains(
+            "<testsuites name=\"reqx\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"0.200\">"
+        ));
+        assert!(xml.contains("<testcase name=\"1. Login\" classname=\"api.reqx\" time=\"0.120\"/>"));
+        assert!(xml.contains("name=\"2. Create &lt;user&gt;\""));
+        assert!(xml.contains(
+            "<failure message=\"expected status == 201, got 400\" type=\"AssertionFailure\">"
+        ));
+        assert!(xml.contains("<system-out>{&quot;error&quot;: &quot;bad&quot;}</system-out>"));
+        assert!(xml.contains("<skipped message=\"not run\"/>"));
+    }
+
+    #[test]
+    fn test_tap() {
+        let out = tap(&suites());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "TAP version 13");
+        assert_eq!(lines[1], "1..3");
+        assert_eq!(lines[2], "ok 1 api.reqx - 1. Login");
+        assert!(out.contains("not ok 2 api.reqx - 2. Create <user>\n  ---\n  message: \"expected status == 201, got 400\"\n  severity: fail\n  duration_ms: 80\n  response: |\n    {\"error\": \"bad\"}\n  ...\n"));
+        assert!(out.contains("ok 3 api.reqx - 3. Delete # SKIP not run"));
+    }
+}
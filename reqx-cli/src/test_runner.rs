@@ -0,0 +1,329 @@
+//! `reqx test`: run `.reqx` files as test suites, one test case per request.
+
+use crate::diagnostic;
+use crate::environment;
+use crate::reqwest_client::ReqwestClient;
+use crate::test_report::{self, Outcome, TestCase, TestSuite};
+use clap::Args;
+use colored::Colorize;
+use reqx_core::ast::Request;
+use reqx_core::error::ReqxError;
+use reqx_core::report::RequestReport;
+use reqx_core::reporter::Reporter;
+use reqx_core::{interpreter, lexer, parser};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Longest response excerpt kept in the reports.
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+#[derive(Args, Debug)]
+pub struct TestArgs {
+    /// The .reqx files to run
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Write a JUnit XML report to this path
+    #[arg(long, value_name = "PATH")]
+    junit: Option<String>,
+
+    /// Write a TAP report to this path (`-` for stdout)
+    #[arg(long, value_name = "PATH")]
+    tap: Option<String>,
+
+    /// Seed the random values of `{{$uuid}}`, `{{$randomInt}}` and `{{$randomString}}`
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Read `{{$env.NAME}}` fallbacks from this file instead of the `.env` next to each .reqx file
+    #[arg(long, value_name = "PATH")]
+    env_file: Option<String>,
+
+    /// Use the variables of this environment from reqx.env.json
+    #[arg(short = 'e', long = "env", value_name = "NAME")]
+    environment: Option<String>,
+
+    /// Set a variable, overriding the file and the environment (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = environment::parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Set variables from a JSON object, overriding the file and the environment
+    #[arg(long, value_name = "PATH")]
+    vars_file: Option<String>,
+}
+
+/// Run every file and write the requested reports. Returns the process exit code.
+pub fn run(args: TestArgs) -> i32 {
+    let client = ReqwestClient::new();
+    let overrides = match environment::overrides(args.vars_file.as_deref(), &args.vars) {
+        Ok(overrides) => overrides,
+        Err(message) => {
+            eprintln!("{} {}", "✖".red().bold(), message);
+            return 1;
+        }
+    };
+    // A request that errors is one failed test; the others still run.
+    let options = interpreter::ExecuteOptions {
+        keep_going: true,
+        seed: args.seed,
+        overrides,
+        ..Default::default()
+    };
+    let suites: Vec<TestSuite> = args
+        .files
+        .iter()
+        .map(|path| run_file(&client, path, &args, &options))
+        .collect();
+
+    let reports = [
+        (&args.junit, test_report::junit(&suites)),
+        (&args.tap, test_report::tap(&suites)),
+    ];
+    for (path, contents) in reports {
+        let Some(path) = path else { continue };
+        if path == "-" {
+            print!("{}", contents);
+        } else if let Err(e) = fs::write(path, contents) {
+            eprintln!(
+                "{} Error writing report '{}': {}",
+                "✖".red().bold(),
+                path.bold(),
+                e
+            );
+            return 1;
+        }
+    }
+
+    let total: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let failed: usize = suites.iter().map(|s| s.failures() + s.errors()).sum();
+    let skipped: usize = suites.iter().map(TestSuite::skipped).sum();
+    let summary = format!(
+        "{} test(s): {} passed, {} failed, {} skipped",
+        total,
+        total - failed - skipped,
+        failed,
+        skipped
+    );
+    if failed > 0 {
+        eprintln!("\n{}", summary.red().bold());
+        1
+    } else {
+        eprintln!("\n{}", summary.green().bold());
+        0
+    }
+}
+
+fn run_file(
+    client: &ReqwestClient,
+    path: &str,
+    args: &TestArgs,
+    options: &interpreter::ExecuteOptions,
+) -> TestSuite {
+    eprintln!("{}", path.bold());
+    let mut suite = TestSuite {
+        name: path.to_string(),
+        cases: Vec::new(),
+    };
+
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            push_case(
+                &mut suite,
+                error_case("load", format!("Error reading file: {}", e)),
+            );
+            return suite;
+        }
+    };
+
+    let loaded = environment::dotenv(path, args.env_file.as_deref()).and_then(|env| {
+        Ok((
+            env,
+            environment::environment(path, args.environment.as_deref())?,
+        ))
+    });
+    let options = match loaded {
+        Ok((env, layers)) => interpreter::ExecuteOptions {
+            env,
+            environment: layers,
+            base_dir: Path::new(path).parent().unwrap_or(Path::new("")).into(),
+            ..options.clone()
+        },
+        Err(message) => {
+            push_case(&mut suite, error_case("load", message));
+            return suite;
+        }
+    };
+
+    let tokens = match lexer::tokenize(&contents) {
+        Ok(t) => t,
+        Err(e) => {
+            push_case(&mut suite, file_error_case(&e, path, &contents));
+            return suite;
+        }
+    };
+
+    // Broken requests become errors; the valid ones still run.
+    let output = parser::parse_recovering(tokens);
+    for e in &output.errors {
+        push_case(&mut suite, file_error_case(e, path, &contents));
+    }
+    let file = output.file;
+
+    let mut collector = CaseCollector {
+        suite: &mut suite,
+        path,
+        contents: &contents,
+        current: None,
+    };
+    // Request errors become cases as they happen; only a bad selection is
+    // left, before any request runs.
+    if let Err(e) = interpreter::execute(client, &file, &mut collector, &options) {
+        let e = e.with_path(path);
+        diagnostic::print(&e, &contents);
+        push_case(&mut suite, error_case("run", e.to_string()));
+    }
+
+    suite
+}
+
+/// Turns interpreter events into test cases.
+struct CaseCollector<'a> {
+    suite: &'a mut TestSuite,
+    /// The file being run and its contents, for diagnostics.
+    path: &'a str,
+    contents: &'a str,
+    /// The test name of the request being executed.
+    current: Option<String>,
+}
+
+impl Reporter for CaseCollector<'_> {
+    fn request_started(&mut self, index: usize, _total: usize, request: &Request) {
+        self.current = Some(case_name(index, request));
+    }
+
+    fn request_failed(&mut self, index: usize, request: &Request, error: &ReqxError) {
+        let error = error.clone().with_path(self.path);
+        diagnostic::print(&error, self.contents);
+        self.current = None;
+        push_case(
+            self.suite,
+            error_case(&case_name(index, request), error.to_string()),
+        );
+    }
+
+    fn request_finished(&mut self, report: &RequestReport) {
+        let Some(name) = self.current.take() else {
+            return;
+        };
+        push_case(self.suite, request_case(name, report));
+    }
+}
+
+fn case_name(index: usize, request: &Request) -> String {
+    let label = request
+        .name
+        .clone()
+        .or_else(|| request.comment.clone())
+        .unwrap_or_else(|| format!("{} {}", request.method, request.url));
+    format!("{}. {}", index + 1, label)
+}
+
+/// A request passes if all its assertions pass or, without assertions,
+/// if its status is not 4xx/5xx.
+fn request_case(name: String, report: &RequestReport) -> TestCase {
+    let Some(ref response) = report.response else {
+        return TestCase {
+            name,
+            duration: Duration::ZERO,
+            outcome: Outcome::Skipped("not sent".to_string()),
+            output: None,
+        };
+    };
+
+    let outcome = if !report.assertions.is_empty() {
+        let failures: Vec<String> = report
+            .assertions
+            .iter()
+            .filter(|a| !a.passed)
+            .map(|a| {
+                format!(
+                    "line {}: {} ({})",
+                    a.line,
+                    a.text,
+                    a.message.as_deref().unwrap_or("failed")
+                )
+            })
+            .collect();
+        if failures.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed(failures.join("\n"))
+        }
+    } else if response.status_is_client_error || response.status_is_server_error {
+        Outcome::Failed(format!("HTTP status {}", response.status))
+    } else {
+        Outcome::Passed
+    };
+
+    let output = (!response.body.is_empty()).then(|| {
+        if response.is_binary() {
+            return format!("(binary, {} bytes)", response.body.len());
+        }
+        let body = response.text();
+        let mut snippet: String = body.chars().take(MAX_OUTPUT_CHARS).collect();
+        if snippet.len() < body.len() {
+            snippet.push_str("\n...");
+        }
+        snippet
+    });
+
+    TestCase {
+        name,
+        duration: report.duration.unwrap_or_default(),
+        outcome,
+        output,
+    }
+}
+
+fn error_case(name: &str, message: String) -> TestCase {
+    TestCase {
+        name: name.to_string(),
+        duration: Duration::ZERO,
+        outcome: Outcome::Error(message),
+        output: None,
+    }
+}
+
+/// A lexer or parser error, printed as a diagnostic and recorded as an errored case.
+fn file_error_case(e: &ReqxError, path: &str, contents: &str) -> TestCase {
+    let e = e.clone().with_path(path);
+    diagnostic::print(&e, contents);
+    let name = match e.location() {
+        Some(location) => format!("parse error at line {}", location.span.line),
+        None => "parse error".to_string(),
+    };
+    error_case(&name, e.to_string())
+}
+
+/// Record a case and print a progress line for it.
+fn push_case(suite: &mut TestSuite, case: TestCase) {
+    let time = format!("({} ms)", case.duration.as_millis()).dimmed();
+    match &case.outcome {
+        Outcome::Passed => eprintln!("  {} {} {}", "✔".green().bold(), case.name, time),
+        Outcome::Failed(message) | Outcome::Error(message) => {
+            eprintln!("  {} {} {}", "✖".red().bold(), case.name.red(), time);
+            for line in message.lines() {
+                eprintln!("      {}", line.dimmed());
+            }
+        }
+        Outcome::Skipped(message) => eprintln!(
+            "  {} {} {}",
+            "-".yellow().bold(),
+            case.name,
+            format!("(skipped: {})", message).dimmed()
+        ),
+    }
+    suite.cases.push(case);
+}
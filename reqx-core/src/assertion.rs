@@ -0,0 +1,257 @@
+//! Evaluation of response assertions: `? status == 201`.
+
+use crate::ast::{Assertion, AssertionOperator, AssertionSubject};
+use crate::client::HttpResponse;
+use crate::jsonpath;
+use crate::report::AssertionResult;
+use std::time::Duration;
+
+/// Check `assertion` against a response. `expected` is the interpolated right-hand side.
+pub(crate) fn evaluate(
+    assertion: &Assertion,
+    expected: &str,
+    response: &HttpResponse,
+    duration: Duration,
+) -> AssertionResult {
+    let mut result = AssertionResult {
+        text: assertion.text.clone(),
+        line: assertion.line,
+        expected: expected.to_string(),
+        actual: None,
+        passed: false,
+        message: None,
+    };
+
+    let (actual, expected) = match actual_value(assertion, response, duration) {
+        Ok(actual) => (actual, expected.to_string()),
+        Err(message) => {
+            result.message = Some(message);
+            return result;
+        }
+    };
+    let expected = if assertion.subject == AssertionSubject::Duration
+        && assertion.operator != AssertionOperator::Exists
+    {
+        match parse_duration_ms(&expected) {
+            Some(ms) => ms.to_string(),
+            None => {
+                result.message = Some(format!("invalid duration `{}`", expected));
+                return result;
+            }
+        }
+    } else {
+        expected
+    };
+    result.actual = actual.clone();
+
+    let Some(actual) = actual else {
+        result.message = Some(format!("{} not found", describe(&assertion.subject)));
+        return result;
+    };
+
+    let outcome = match assertion.operator {
+        AssertionOperator::Exists => Ok(true),
+        AssertionOperator::Eq => Ok(values_equal(&actual, &expected)),
+        AssertionOperator::Ne => Ok(!values_equal(&actual, &expected)),
+        AssertionOperator::Contains => Ok(actual.contains(&expected)),
+        AssertionOperator::NotContains => Ok(!actual.contains(&expected)),
+        AssertionOperator::Lt
+        | AssertionOperator::Le
+        | AssertionOperator::Gt
+        | AssertionOperator::Ge => match (as_number(&actual), as_number(&expected)) {
+            (Some(a), Some(e)) => Ok(match assertion.operator {
+                AssertionOperator::Lt => a < e,
+                AssertionOperator::Le => a <= e,
+                AssertionOperator::Gt => a > e,
+                _ => a >= e,
+            }),
+            _ => Err(format!(
+                "cannot compare `{}` with `{}`: both sides must be numbers",
+                actual, expected
+            )),
+        },
+    };
+
+    match outcome {
+        Ok(true) => result.passed = true,
+        Ok(false) => {
+            result.message = Some(format!(
+                "expected {} {} {}, got {}",
+                describe(&assertion.subject),
+                assertion.operator,
+                expected,
+                actual
+            ))
+        }
+        Err(message) => result.message = Some(message),
+    }
+    result
+}
+
+/// The value an assertion subject refers to, `None` if it is missing from the
+/// response. For `exists`, a JSON path that matches nothing is missing even
+/// when it would select an empty array.
+fn actual_value(
+    assertion: &Assertion,
+    response: &HttpResponse,
+    duration: Duration,
+) -> Result<Option<String>, String> {
+    match &assertion.subject {
+        AssertionSubject::Status => Ok(Some(response.status.to_string())),
+        AssertionSubject::Duration => Ok(Some(duration.as_millis().to_string())),
+        AssertionSubject::Header(name) => {
+            let values: Vec<&str> = response
+                .headers
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+                .collect();
+            Ok((!values.is_empty()).then(|| values.join(", ")))
+        }
+        AssertionSubject::Body(None) => {
+            Ok((!response.body.is_empty()).then(|| response.text().into_owned()))
+        }
+        AssertionSubject::Body(Some(path)) => {
+            let json: serde_json::Value = serde_json::from_str(&response.text())
+                .map_err(|_| "response body is not valid JSON".to_string())?;
+            if assertion.operator == AssertionOperator::Exists {
+                let value = jsonpath::select_match(&json, path).map_err(|e| e.message)?;
+                return Ok(value.map(|value| jsonpath::to_text(&value)));
+            }
+            jsonpath::select_text(&json, path).map_err(|e| e.message)
+        }
+    }
+}
+
+fn describe(subject: &AssertionSubject) -> String {
+    match subject {
+        AssertionSubject::Status => "status".to_string(),
+        AssertionSubject::Duration => "duration".to_string(),
+        AssertionSubject::Header(name) => format!("header {}", name),
+        AssertionSubject::Body(None) => "body".to_string(),
+        AssertionSubject::Body(Some(path)) => format!("body.{}", path),
+    }
+}
+
+fn as_number(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok()
+}
+
+/// Numbers compare numerically (`42 == 42.0`), everything else as text.
+fn values_equal(actual: &str, expected: &str) -> bool {
+    match (as_number(actual), as_number(expected)) {
+        (Some(a), Some(e)) => a == e,
+        _ => actual == expected,
+    }
+}
+
+/// `500ms`, `2s`, `1.5s` or a bare number of milliseconds.
+fn parse_duration_ms(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Some(ms) = s.strip_suffix("ms") {
+        as_number(ms)
+    } else if let Some(secs) = s.strip_suffix('s') {
+        as_number(secs).map(|v| v * 1000.0)
+    } else {
+        as_number(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Span;
+
+    fn assertion(subject: AssertionSubject, operator: AssertionOperator) -> Assertion {
+        Assertion {
+            subject,
+            operator,
+            expected: String::new(),
+            text: String::new(),
+            line: 1,
+            span: Span::default(),
+            expected_span: Span::default(),
+        }
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 201,
+            status_is_success: true,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: vec![(
+                "content-type".to_string(),
+                "application/json; charset=utf-8".to_string(),
+            )],
+            body: r#"{"user": {"id": 42, "name": "Ada"}, "tags": []}"#.into(),
+        }
+    }
+
+    fn check(subject: AssertionSubject, operator: AssertionOperator, expected: &str) -> bool {
+        evaluate(
+            &assertion(subject, operator),
+            expected,
+            &response(),
+            Duration::from_millis(120),
+        )
+        .passed
+    }
+
+    #[test]
+    fn test_status_and_duration() {
+        use AssertionOperator::*;
+        assert!(check(AssertionSubject::Status, Eq, "201"));
+        assert!(!check(AssertionSubject::Status, Eq, "200"));
+        assert!(check(AssertionSubject::Status, Lt, "300"));
+        assert!(check(AssertionSubject::Duration, Lt, "500ms"));
+        assert!(check(AssertionSubject::Duration, Lt, "1s"));
+        assert!(!check(AssertionSubject::Duration, Ge, "0.5s"));
+    }
+
+    #[test]
+    fn test_header_and_body() {
+        use AssertionOperator::*;
+        let header = || AssertionSubject::Header("Content-Type".to_string());
+        assert!(check(header(), Contains, "json"));
+        assert!(check(header(), NotContains, "xml"));
+        assert!(!check(
+            AssertionSubject::Header("X-Missing".to_string()),
+            Exists,
+            ""
+        ));
+
+        let path = |p: &str| AssertionSubject::Body(Some(p.to_string()));
+        assert!(check(path("user.id"), Eq, "42"));
+        assert!(check(path("user.name"), Ne, "Bob"));
+        assert!(check(AssertionSubject::Body(None), Contains, "Ada"));
+        assert!(!check(path("user.email"), Exists, ""));
+        assert!(check(path("tags"), Exists, ""));
+        assert!(!check(path("$.tags[*].name"), Exists, ""));
+        assert!(check(path("$.tags[*].name"), Eq, "[]"));
+    }
+
+    #[test]
+    fn test_failure_messages() {
+        let result = evaluate(
+            &assertion(AssertionSubject::Status, AssertionOperator::Eq),
+            "200",
+            &response(),
+            Duration::ZERO,
+        );
+        assert_eq!(result.actual.as_deref(), Some("201"));
+        assert_eq!(result.message.unwrap(), "expected status == 200, got 201");
+
+        let result = evaluate(
+            &assertion(
+                AssertionSubject::Body(Some("user.name".to_string())),
+                AssertionOperator::Gt,
+            ),
+            "3",
+            &response(),
+            Duration::ZERO,
+        );
+        assert!(!result.passed);
+        assert!(result.message.unwrap().contains("must be numbers"));
+    }
+}
@@ -1,5 +1,7 @@
 //! AST types for the `.reqx` DSL.
 
+use crate::span::Span;
+
 /// Represents a parsed `.reqx` file.
 #[derive(Debug, Clone)]
 pub struct ReqxFile {
@@ -13,25 +15,220 @@ pub struct Variable {
     pub name: String,
     pub value: String,
     pub line: usize,
+    /// The whole definition line.
+    pub span: Span,
+    /// Just the value, after `=`.
+    pub value_span: Span,
 }
 
 /// An HTTP request block.
 #[derive(Debug, Clone)]
 pub struct Request {
     pub comment: Option<String>,
+    /// Set with `# @name login`; unique within a file.
+    pub name: Option<String>,
     pub method: HttpMethod,
     pub url: String,
+    /// The indented `?key=value` / `&key=value` lines below the URL, appended
+    /// to it percent-encoded.
+    pub query: Vec<Param>,
     pub headers: Vec<Header>,
-    pub body: Option<String>,
-    pub extracts: Vec<Variable>,
+    pub body: Option<Body>,
+    /// Set with `# @no-interpolate`: the body is sent as written, `{{` and all.
+    pub no_interpolate: bool,
+    pub extracts: Vec<Extract>,
+    pub assertions: Vec<Assertion>,
+    /// `> path` or `>> path`: where to save the response body.
+    pub output: Option<Output>,
     pub line: usize,
+    /// The request line (`METHOD url` or just `url`).
+    pub span: Span,
+    pub url_span: Span,
+    /// From the first to the last body line.
+    pub body_span: Option<Span>,
 }
 
-/// An HTTP header: `Key: Value`
+/// The body of a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    /// Text written below the headers.
+    Text(String),
+    /// `< ./payload.json`, sent as is, or `<@ ./template.json`, whose contents
+    /// are interpolated. The path is relative to the `.reqx` file.
+    File { path: String, interpolate: bool },
+    /// `multipart/form-data` parts, each starting with a `--- name` line. The
+    /// boundary and `Content-Type` header are generated when sending.
+    Multipart(Vec<Part>),
+    /// `application/x-www-form-urlencoded` fields, one `&key=value` per line.
+    Form(Vec<Param>),
+    /// The GraphQL document of a `GRAPHQL` request (or one with an
+    /// `X-Request-Type: GraphQL` header) and its JSON variables, if any, with
+    /// where they start. Sent as `{"query": ..., "variables": ...}`.
+    Graphql {
+        query: String,
+        variables: Option<(String, Span)>,
+    },
+}
+
+/// A part of a multipart body: a `--- name` line, optional headers followed
+/// by a blank line, then the part's text or `< file`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: String,
+    pub headers: Vec<Header>,
+    /// `Body::Text` or `Body::File`.
+    pub body: Body,
+    /// The `--- name` line.
+    pub span: Span,
+    /// From the first to the last line of the part's text.
+    pub body_span: Span,
+}
+
+/// A value captured from the response: `@name = source`, or `@name? = source`
+/// when the value may be missing.
+#[derive(Debug, Clone)]
+pub struct Extract {
+    pub name: String,
+    pub source: ExtractSource,
+    /// The source as written, after `=`.
+    pub text: String,
+    /// Whether a missing value is allowed instead of an error.
+    pub optional: bool,
+    pub line: usize,
+    pub span: Span,
+    /// Just the source, after `=`.
+    pub value_span: Span,
+}
+
+/// Where an extraction takes its value from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtractSource {
+    /// `user.id` or `$.users[0].id`: a JSONPath into the JSON body
+    JsonPath(String),
+    /// `header Location`
+    Header(String),
+    /// `status`
+    Status,
+    /// `cookie session_id`, from the `Set-Cookie` headers
+    Cookie(String),
+    /// `regex id="(\d+)"`: the first capture group (or the whole match) in the raw body
+    Regex(String),
+    /// `xpath //user/@id`, or any source starting with `/`: an XPath into an
+    /// XML or HTML body
+    XPath(String),
+    /// `css h1.title` for the element's text, or `css a.next @href` for one
+    /// of its attributes
+    Css {
+        selector: String,
+        attribute: Option<String>,
+    },
+}
+
+/// A check on the response: `? subject operator expected`
 #[derive(Debug, Clone)]
+pub struct Assertion {
+    pub subject: AssertionSubject,
+    pub operator: AssertionOperator,
+    /// The right-hand side, which may contain `{{vars}}`. Empty for `exists`.
+    pub expected: String,
+    /// The assertion as written, without the leading `?`.
+    pub text: String,
+    pub line: usize,
+    pub span: Span,
+    pub expected_span: Span,
+}
+
+/// What an assertion inspects.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssertionSubject {
+    /// `status`
+    Status,
+    /// `header Content-Type`
+    Header(String),
+    /// `body` (the raw body) or `body.user.id` (a JSON path)
+    Body(Option<String>),
+    /// `duration`, in milliseconds
+    Duration,
+}
+
+/// How the subject is compared with the expected value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    NotContains,
+    Exists,
+}
+
+impl std::fmt::Display for AssertionOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AssertionOperator::Eq => "==",
+            AssertionOperator::Ne => "!=",
+            AssertionOperator::Lt => "<",
+            AssertionOperator::Le => "<=",
+            AssertionOperator::Gt => ">",
+            AssertionOperator::Ge => ">=",
+            AssertionOperator::Contains => "contains",
+            AssertionOperator::NotContains => "!contains",
+            AssertionOperator::Exists => "exists",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::str::FromStr for AssertionOperator {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "==" => Ok(AssertionOperator::Eq),
+            "!=" => Ok(AssertionOperator::Ne),
+            "<" => Ok(AssertionOperator::Lt),
+            "<=" => Ok(AssertionOperator::Le),
+            ">" => Ok(AssertionOperator::Gt),
+            ">=" => Ok(AssertionOperator::Ge),
+            "contains" => Ok(AssertionOperator::Contains),
+            "!contains" => Ok(AssertionOperator::NotContains),
+            "exists" => Ok(AssertionOperator::Exists),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An HTTP header: `Key: Value`
+#[derive(Debug, Clone, PartialEq)]
 pub struct Header {
     pub key: String,
     pub value: String,
+    pub key_span: Span,
+    pub value_span: Span,
+}
+
+/// An output redirect: `> path` overwrites the file with the response body,
+/// `>> path` appends to it. The path may contain `{{variables}}` and is
+/// relative to the `.reqx` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    pub path: String,
+    pub append: bool,
+    /// The whole `> path` line.
+    pub span: Span,
+    pub path_span: Span,
+}
+
+/// A `key=value` pair of a query block or form body, before percent-encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub key: String,
+    pub value: String,
+    pub key_span: Span,
+    pub value_span: Span,
 }
 
 /// Supported HTTP methods.
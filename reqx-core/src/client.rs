@@ -1,4 +1,6 @@
 use crate::ast::HttpMethod;
+use encoding_rs::{Encoding, UTF_8};
+use std::borrow::Cow;
 
 /// Output of a completed HTTP request.
 #[derive(Debug, Clone)]
@@ -8,18 +10,139 @@ pub struct HttpResponse {
     pub status_is_client_error: bool,
     pub status_is_server_error: bool,
     pub headers: Vec<(String, String)>,
-    pub body: String,
+    /// The body as received. Use `text` to read it as text.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// The first value of header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The media type of the `Content-Type` header, without its parameters,
+    /// such as `application/json`.
+    pub fn content_type(&self) -> Option<&str> {
+        let media_type = self.header("content-type")?.split(';').next()?.trim();
+        (!media_type.is_empty()).then_some(media_type)
+    }
+
+    /// The `charset` parameter of the `Content-Type` header, such as `utf-8`.
+    pub fn charset(&self) -> Option<&str> {
+        self.header("content-type")?
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value.trim().trim_matches('"'))
+    }
+
+    /// The body decoded as text, in its `charset` or else UTF-8. Invalid
+    /// bytes are replaced with `�`, so check `is_binary` first when the body
+    /// may not be text.
+    pub fn text(&self) -> Cow<'_, str> {
+        let encoding = self
+            .charset()
+            .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+            .unwrap_or(UTF_8);
+        encoding.decode(&self.body).0
+    }
+
+    /// Whether the body is binary data, such as an image or a compressed
+    /// download, rather than text: according to the `Content-Type` and
+    /// `Content-Encoding` headers or, without a `Content-Type`, the bytes.
+    pub fn is_binary(&self) -> bool {
+        if self
+            .header("content-encoding")
+            .is_some_and(|e| !e.trim().eq_ignore_ascii_case("identity"))
+        {
+            return true;
+        }
+        match self.content_type().map(str::to_ascii_lowercase) {
+            Some(media_type) => !is_text_type(&media_type),
+            None => {
+                self.body.contains(&0)
+                    || (self.charset().is_none() && std::str::from_utf8(&self.body).is_err())
+            }
+        }
+    }
+}
+
+/// Whether a media type is text: `text/*`, or JSON, XML, JavaScript, YAML and
+/// form data under any type.
+fn is_text_type(media_type: &str) -> bool {
+    media_type.starts_with("text/")
+        || ["json", "xml", "javascript", "yaml", "x-www-form-urlencoded"]
+            .iter()
+            .any(|kind| media_type.contains(kind))
 }
 
 /// A generic interface to execute an HTTP request.
 /// Your application can implement this trait and pass it to `interpreter::execute`
 /// to decouple `reqx` from any specific HTTP library.
+///
+/// Request and response bodies are bytes, since they may be binary.
 pub trait HttpClient {
     fn execute(
         &self,
         method: &HttpMethod,
         url: &str,
         headers: &[(String, String)],
-        body: Option<&str>,
+        body: Option<&[u8]>,
     ) -> Result<HttpResponse, String>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(headers: &[(&str, &str)], body: &[u8]) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            status_is_success: true,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_content_type_and_charset() {
+        let r = response(
+            &[("Content-Type", "text/html; charset=\"ISO-8859-1\"")],
+            b"",
+        );
+        assert_eq!(r.content_type(), Some("text/html"));
+        assert_eq!(r.charset(), Some("ISO-8859-1"));
+        assert_eq!(response(&[], b"").content_type(), None);
+    }
+
+    #[test]
+    fn test_text_decoding() {
+        let latin1 = response(
+            &[("Content-Type", "text/plain; charset=iso-8859-1")],
+            b"caf\xe9",
+        );
+        assert_eq!(latin1.text(), "café");
+        let utf8 = response(&[], "café".as_bytes());
+        assert!(matches!(utf8.text(), Cow::Borrowed("café")));
+        assert_eq!(response(&[], b"a\xffb").text(), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn test_is_binary() {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        assert!(response(&[("Content-Type", "image/png")], &png).is_binary());
+        assert!(response(&[], &png).is_binary());
+        assert!(response(&[("Content-Encoding", "gzip")], b"{}").is_binary());
+        assert!(!response(&[("Content-Type", "application/problem+json")], b"{}").is_binary());
+        assert!(!response(&[], b"plain text").is_binary());
+    }
+}
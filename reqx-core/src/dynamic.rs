@@ -0,0 +1,387 @@
+//! Built-in dynamic variables: `{{$uuid}}`, `{{$timestamp}}`,
+//! `{{$randomInt 1 100}}`, `{{$env.API_TOKEN}}` and friends. They start with
+//! `$`, so they never clash with `@name = value` variables.
+
+use crate::interpreter::closest_name;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Duration, SecondsFormat, Utc};
+use rand::distributions::Alphanumeric;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The built-in variables, for suggestions.
+const NAMES: &[&str] = &[
+    "uuid",
+    "timestamp",
+    "isoTimestamp",
+    "randomInt",
+    "randomString",
+    "datetime",
+];
+
+pub(crate) const DYNAMIC_HELP: &str = "built-in variables are `{{$uuid}}`, `{{$timestamp}}`, `{{$isoTimestamp}}`, `{{$randomInt 1 100}}`, `{{$randomString 12}}`, `{{$datetime \"%Y-%m-%d\" -1d}}` and `{{$env.NAME}}`";
+
+/// Why a built-in variable could not be evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DynamicError {
+    pub message: String,
+    pub help: String,
+}
+
+/// Evaluates built-in variables, drawing random values from one generator so
+/// that a seeded run always produces the same values.
+pub(crate) struct Dynamic {
+    rng: RefCell<StdRng>,
+    /// Reads a variable of the process environment.
+    process_env: fn(&str) -> Option<String>,
+    /// `$env` values used when the process environment does not set them.
+    env: HashMap<String, String>,
+}
+
+impl Dynamic {
+    /// A generator seeded with `seed`, or from system entropy without one.
+    pub fn new(seed: Option<u64>, env: HashMap<String, String>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            rng: RefCell::new(rng),
+            process_env: |name| std::env::var(name).ok(),
+            env,
+        }
+    }
+
+    /// Evaluate `expr`, the placeholder contents after `$`, such as
+    /// `randomInt 1 100`.
+    pub fn evaluate(&self, expr: &str) -> Result<String, DynamicError> {
+        if expr == "env" {
+            return self.env_var("");
+        }
+        if let Some(name) = expr.strip_prefix("env.") {
+            return self.env_var(name);
+        }
+        let (name, rest) = expr
+            .split_once(char::is_whitespace)
+            .map_or((expr, ""), |(name, rest)| (name, rest.trim()));
+        let args = split_args(rest).map_err(|message| invalid(name, message))?;
+        let no_args = || match args.is_empty() {
+            true => Ok(()),
+            false => Err(invalid(name, "it takes no arguments".to_string())),
+        };
+        let mut rng = self.rng.borrow_mut();
+
+        match name {
+            "uuid" => {
+                no_args()?;
+                let bytes = rng.gen::<[u8; 16]>();
+                Ok(uuid::Builder::from_random_bytes(bytes)
+                    .into_uuid()
+                    .to_string())
+            }
+            "timestamp" => {
+                no_args()?;
+                Ok(Utc::now().timestamp().to_string())
+            }
+            "isoTimestamp" => {
+                no_args()?;
+                Ok(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true))
+            }
+            "randomInt" => {
+                let (min, max) = match args.as_slice() {
+                    [] => (0, 1000),
+                    [min, max] => (
+                        parse_int(min).map_err(|m| invalid(name, m))?,
+                        parse_int(max).map_err(|m| invalid(name, m))?,
+                    ),
+                    _ => {
+                        return Err(invalid(
+                            name,
+                            "it takes a minimum and a maximum".to_string(),
+                        ))
+                    }
+                };
+                if min > max {
+                    return Err(invalid(
+                        name,
+                        format!("the minimum {} is larger than the maximum {}", min, max),
+                    ));
+                }
+                Ok(rng.gen_range(min..=max).to_string())
+            }
+            "randomString" => {
+                let length = match args.as_slice() {
+                    [] => 10,
+                    [length] => length.parse::<usize>().map_err(|_| {
+                        invalid(name, format!("`{}` is not a valid length", length))
+                    })?,
+                    _ => return Err(invalid(name, "it takes a single length".to_string())),
+                };
+                Ok(random_string(&mut rng, length))
+            }
+            "datetime" => {
+                let (format, offset) = match args.as_slice() {
+                    [format] => (format, None),
+                    [format, offset] => (format, Some(offset)),
+                    _ => {
+                        return Err(invalid(
+                            name,
+                            "it takes a format and an optional offset such as `-1d`".to_string(),
+                        ))
+                    }
+                };
+                let time = match offset {
+                    Some(offset) => {
+                        let duration = parse_offset(offset).map_err(|m| invalid(name, m))?;
+                        Utc::now().checked_add_signed(duration).ok_or_else(|| {
+                            invalid(name, format!("the offset `{}` is out of range", offset))
+                        })?
+                    }
+                    None => Utc::now(),
+                };
+                match format.as_str() {
+                    "iso8601" => Ok(time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                    "rfc1123" => Ok(time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()),
+                    format => {
+                        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                            return Err(invalid(name, format!("unknown format `{}`", format)));
+                        }
+                        Ok(time.format(format).to_string())
+                    }
+                }
+            }
+            _ => {
+                let names = NAMES.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+                let help = match closest_name(name, names.iter()) {
+                    Some(suggestion) => format!("did you mean `{{{{${}}}}}`?", suggestion),
+                    None => DYNAMIC_HELP.to_string(),
+                };
+                Err(DynamicError {
+                    message: format!("unknown built-in variable `${}`", name),
+                    help,
+                })
+            }
+        }
+    }
+
+    /// `$env.NAME`: the process environment first, then the fallback values.
+    fn env_var(&self, name: &str) -> Result<String, DynamicError> {
+        if name.is_empty() {
+            return Err(invalid(
+                "env",
+                "it needs a variable name, as in `{{$env.API_TOKEN}}`".to_string(),
+            ));
+        }
+        (self.process_env)(name)
+            .or_else(|| self.env.get(name).cloned())
+            .ok_or_else(|| DynamicError {
+                message: format!("environment variable `{}` is not set", name),
+                help: format!(
+                    "export it, or add `{}=...` to a `.env` file next to the .reqx file",
+                    name
+                ),
+            })
+    }
+}
+
+/// `length` random letters and digits.
+fn random_string(rng: &mut StdRng, length: usize) -> String {
+    (0..length)
+        .map(|_| rng.sample(Alphanumeric) as char)
+        .collect()
+}
+
+/// An error for arguments `$name` does not accept.
+fn invalid(name: &str, message: String) -> DynamicError {
+    DynamicError {
+        message: format!("invalid `${}`: {}", name, message),
+        help: DYNAMIC_HELP.to_string(),
+    }
+}
+
+/// Split arguments on whitespace; `"double quotes"` group words together.
+pub(crate) fn split_args(text: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut arg = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => arg.push(c),
+                    None => return Err("unclosed `\"`".to_string()),
+                }
+            }
+            args.push(arg);
+        } else {
+            let mut arg = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                arg.push(c);
+            }
+            args.push(arg);
+        }
+    }
+    Ok(args)
+}
+
+fn parse_int(text: &str) -> Result<i64, String> {
+    text.parse()
+        .map_err(|_| format!("`{}` is not a whole number", text))
+}
+
+/// An offset such as `-1d`, `+2h` or `30m`. Units are `s`, `m`, `h`, `d` and `w`.
+fn parse_offset(text: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "`{}` is not a valid offset; use a number and a unit (s, m, h, d or w), e.g. `-1d`",
+            text
+        )
+    };
+    let unit_at = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = text.split_at(unit_at);
+    let amount: i64 = amount
+        .strip_prefix('+')
+        .unwrap_or(amount)
+        .parse()
+        .map_err(|_| invalid())?;
+    let duration = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    duration.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_values_repeat() {
+        let exprs = ["uuid", "randomInt 1 100", "randomString 12"];
+        let run = |seed| {
+            let dynamic = Dynamic::new(Some(seed), HashMap::new());
+            exprs
+                .iter()
+                .map(|e| dynamic.evaluate(e).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_value_shapes() {
+        let dynamic = Dynamic::new(Some(1), HashMap::new());
+        let eval = |e| dynamic.evaluate(e).unwrap();
+
+        let uuid = eval("uuid");
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.as_bytes()[14], b'4');
+
+        for _ in 0..50 {
+            let n: i64 = eval("randomInt 1 3").parse().unwrap();
+            assert!((1..=3).contains(&n));
+        }
+        assert_eq!(eval("randomInt -5 -5"), "-5");
+
+        let s = eval("randomString 12");
+        assert_eq!(s.len(), 12);
+        assert!(s.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        assert!(eval("timestamp").parse::<i64>().unwrap() > 1_600_000_000);
+        assert!(eval("isoTimestamp").ends_with('Z'));
+
+        let today = Utc::now().date_naive();
+        let yesterday = today - Duration::days(1);
+        assert_eq!(
+            eval(r#"datetime "%Y-%m-%d" -1d"#),
+            yesterday.format("%Y-%m-%d").to_string()
+        );
+        assert_eq!(
+            eval("datetime %Y-%m-%d +1w"),
+            (today + Duration::weeks(1)).format("%Y-%m-%d").to_string()
+        );
+        assert!(eval("datetime rfc1123").ends_with(" GMT"));
+    }
+
+    #[test]
+    fn test_env_variables() {
+        let dotenv = HashMap::from([
+            ("REQX_TEST_FROM_PROCESS".to_string(), "dotenv".to_string()),
+            ("REQX_TEST_FROM_DOTENV".to_string(), "dotenv".to_string()),
+        ]);
+        let mut dynamic = Dynamic::new(None, dotenv);
+        dynamic.process_env =
+            |name| (name == "REQX_TEST_FROM_PROCESS").then(|| "process".to_string());
+        assert_eq!(
+            dynamic.evaluate("env.REQX_TEST_FROM_PROCESS").unwrap(),
+            "process"
+        );
+        assert_eq!(
+            dynamic.evaluate("env.REQX_TEST_FROM_DOTENV").unwrap(),
+            "dotenv"
+        );
+
+        let err = dynamic.evaluate("env.REQX_TEST_MISSING").unwrap_err();
+        assert_eq!(
+            err.message,
+            "environment variable `REQX_TEST_MISSING` is not set"
+        );
+        assert!(err.help.contains("`REQX_TEST_MISSING=...`"));
+        assert_eq!(
+            dynamic.evaluate("env.").unwrap_err().message,
+            "invalid `$env`: it needs a variable name, as in `{{$env.API_TOKEN}}`"
+        );
+    }
+
+    #[test]
+    fn test_invalid_dynamic_variables() {
+        let dynamic = Dynamic::new(Some(1), HashMap::new());
+        let err = |e| dynamic.evaluate(e).unwrap_err();
+
+        let unknown = err("uid");
+        assert_eq!(unknown.message, "unknown built-in variable `$uid`");
+        assert_eq!(unknown.help, "did you mean `{{$uuid}}`?");
+        assert_eq!(err("nope").help, DYNAMIC_HELP);
+
+        assert_eq!(
+            err("randomInt 10 1").message,
+            "invalid `$randomInt`: the minimum 10 is larger than the maximum 1"
+        );
+        assert_eq!(
+            err("randomInt one 5").message,
+            "invalid `$randomInt`: `one` is not a whole number"
+        );
+        assert_eq!(
+            err("uuid 4").message,
+            "invalid `$uuid`: it takes no arguments"
+        );
+        assert!(err(r#"datetime "%Y-%m-%d" -1x"#)
+            .message
+            .contains("is not a valid offset"));
+        assert_eq!(
+            err("datetime %Y +100000000w").message,
+            "invalid `$datetime`: the offset `+100000000w` is out of range"
+        );
+        assert_eq!(
+            err("datetime %Q").message,
+            "invalid `$datetime`: unknown format `%Q`"
+        );
+        assert_eq!(
+            err(r#"datetime "%Y"#).message,
+            "invalid `$datetime`: unclosed `\"`"
+        );
+    }
+}
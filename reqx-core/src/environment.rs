@@ -0,0 +1,158 @@
+//! Named environments from a `reqx.env.json` file, so one `.reqx` file can
+//! target dev, staging and prod:
+//!
+//! ```json
+//! {
+//!   "$shared": { "version": "v2" },
+//!   "dev": { "base_url": "http://localhost:3000" },
+//!   "staging": { "base_url": "https://staging.example.com" }
+//! }
+//! ```
+//!
+//! `$shared` applies to every environment, and each environment overrides it.
+
+use crate::interpreter::{closest_name, VariableLayer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// The name of the environment holding defaults for all the others.
+pub const SHARED: &str = "$shared";
+
+/// The environments of a `reqx.env.json` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environments {
+    shared: HashMap<String, Value>,
+    named: BTreeMap<String, HashMap<String, Value>>,
+}
+
+impl Environments {
+    /// Parse the contents of a `reqx.env.json` file. Values keep their JSON
+    /// type, so `"port": 8080` is a number.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| format!("invalid JSON: {}", e))?;
+        let Value::Object(environments) = value else {
+            return Err("expected an object of environments".to_string());
+        };
+
+        let mut parsed = Environments::default();
+        for (name, variables) in environments {
+            let Some(variables) = to_variables(variables) else {
+                return Err(format!(
+                    "environment `{}` must be an object of variables",
+                    name
+                ));
+            };
+            if name == SHARED {
+                parsed.shared = variables;
+            } else {
+                parsed.named.insert(name, variables);
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The names of the environments, in alphabetical order, without `$shared`.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.named.keys().map(String::as_str)
+    }
+
+    /// The variable layers of environment `name`: `$shared`, then `name`.
+    /// Without a name, only `$shared`.
+    pub fn select(&self, name: Option<&str>) -> Result<Vec<VariableLayer>, String> {
+        let mut layers = vec![VariableLayer {
+            name: SHARED.to_string(),
+            values: self.shared.clone(),
+            untyped: false,
+        }];
+        if let Some(name) = name {
+            let Some(values) = self.named.get(name) else {
+                let names: Vec<String> = self.named.keys().cloned().collect();
+                return Err(match closest_name(name, names.iter()) {
+                    Some(suggestion) => format!(
+                        "No environment is named `{}`. Did you mean `{}`?",
+                        name, suggestion
+                    ),
+                    None if names.is_empty() => {
+                        format!("No environment is named `{}`; none are defined.", name)
+                    }
+                    None => format!(
+                        "No environment is named `{}`. Available: {}.",
+                        name,
+                        names.join(", ")
+                    ),
+                });
+            };
+            layers.push(VariableLayer {
+                name: name.to_string(),
+                values: values.clone(),
+                untyped: false,
+            });
+        }
+        Ok(layers)
+    }
+}
+
+/// Parse a JSON object of variables, such as `{"user_id": 42, "host": "a.com"}`.
+pub fn parse_variables(json: &str) -> Result<HashMap<String, Value>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("invalid JSON: {}", e))?;
+    to_variables(value).ok_or_else(|| "expected an object of variables".to_string())
+}
+
+fn to_variables(value: Value) -> Option<HashMap<String, Value>> {
+    match value {
+        Value::Object(variables) => Some(variables.into_iter().collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"{
+        "$shared": {"version": "v2", "base_url": "http://localhost"},
+        "staging": {"base_url": "https://staging.example.com", "port": 8443},
+        "prod": {"base_url": "https://example.com"}
+    }"#;
+
+    #[test]
+    fn test_select_environment() {
+        let envs = Environments::parse(FILE).unwrap();
+        assert_eq!(envs.names().collect::<Vec<_>>(), vec!["prod", "staging"]);
+
+        let layers = envs.select(Some("staging")).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].name, "$shared");
+        assert_eq!(layers[0].values["version"], "v2");
+        assert_eq!(layers[1].name, "staging");
+        assert_eq!(layers[1].values["port"], 8443);
+
+        let layers = envs.select(None).unwrap();
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].values["base_url"], "http://localhost");
+    }
+
+    #[test]
+    fn test_environment_errors() {
+        let envs = Environments::parse(FILE).unwrap();
+        assert_eq!(
+            envs.select(Some("stagin")).unwrap_err(),
+            "No environment is named `stagin`. Did you mean `staging`?"
+        );
+        assert_eq!(
+            envs.select(Some("qa")).unwrap_err(),
+            "No environment is named `qa`. Available: prod, staging."
+        );
+        assert!(Environments::parse("[]").is_err());
+        assert_eq!(
+            parse_variables(r#"{"id": 7, "host": "a.com"}"#).unwrap()["id"],
+            7
+        );
+        assert!(parse_variables(r#"["id"]"#).is_err());
+        assert_eq!(
+            Environments::parse(r#"{"dev": "http://localhost"}"#).unwrap_err(),
+            "environment `dev` must be an object of variables"
+        );
+    }
+}
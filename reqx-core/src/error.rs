@@ -0,0 +1,208 @@
+//! Errors reported by the lexer, parser and interpreter.
+
+use crate::span::Span;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where an error happened: the `.reqx` file (when known) and the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Path of the `.reqx` file. The lexer and parser only see source text,
+    /// so this is filled in later with `ReqxError::with_path`.
+    pub path: Option<PathBuf>,
+    pub span: Span,
+}
+
+impl Location {
+    pub fn new(span: Span) -> Self {
+        Self { path: None, span }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path {
+            Some(ref path) => write!(
+                f,
+                "{}:{}:{}",
+                path.display(),
+                self.span.line,
+                self.span.column
+            ),
+            None => write!(f, "line {}, column {}", self.span.line, self.span.column),
+        }
+    }
+}
+
+/// Every error `reqx-core` can produce.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReqxError {
+    /// A line the lexer could not tokenize, e.g. `@name` without `=`.
+    Lex {
+        message: String,
+        help: Option<String>,
+        location: Location,
+    },
+    /// A token sequence that does not form a valid request.
+    Parse {
+        message: String,
+        help: Option<String>,
+        location: Location,
+    },
+    /// `{{name}}` refers to a variable that was never defined.
+    /// `suggestion` is the closest defined variable name, if any is close enough.
+    UndefinedVariable {
+        name: String,
+        suggestion: Option<String>,
+        location: Location,
+    },
+    /// `{{login.response...}}` cannot be resolved: the response part is unknown
+    /// or missing, or requests reference each other in a cycle.
+    Reference {
+        message: String,
+        help: Option<String>,
+        location: Location,
+    },
+    /// `{{$name}}` is not a built-in variable, or its arguments are invalid.
+    Builtin {
+        message: String,
+        help: Option<String>,
+        location: Location,
+    },
+    /// A `{{value | name}}` filter is unknown, its arguments are invalid or it
+    /// cannot be applied to the value.
+    Filter {
+        message: String,
+        help: Option<String>,
+        location: Location,
+    },
+    /// A file the request needs, such as a `< path` body, cannot be read, or
+    /// its response cannot be saved to a `> path` output.
+    File { message: String, location: Location },
+    /// A required `@name = source` extraction found nothing in the response.
+    /// The location is the source, after `=`.
+    Extraction {
+        name: String,
+        message: String,
+        location: Location,
+    },
+    /// `{{` without a matching `}}`. `text` is the unterminated placeholder.
+    UnclosedInterpolation { text: String, location: Location },
+    /// The `HttpClient` failed to send the request. The location is the request line.
+    Transport { message: String, location: Location },
+    /// The request index or method filter is invalid. Not tied to any source text.
+    InvalidSelection { message: String },
+}
+
+impl ReqxError {
+    /// The source location of the error, if it has one.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            ReqxError::Lex { location, .. }
+            | ReqxError::Parse { location, .. }
+            | ReqxError::UndefinedVariable { location, .. }
+            | ReqxError::Reference { location, .. }
+            | ReqxError::Builtin { location, .. }
+            | ReqxError::Filter { location, .. }
+            | ReqxError::File { location, .. }
+            | ReqxError::Extraction { location, .. }
+            | ReqxError::UnclosedInterpolation { location, .. }
+            | ReqxError::Transport { location, .. } => Some(location),
+            ReqxError::InvalidSelection { .. } => None,
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            ReqxError::Lex { location, .. }
+            | ReqxError::Parse { location, .. }
+            | ReqxError::UndefinedVariable { location, .. }
+            | ReqxError::Reference { location, .. }
+            | ReqxError::Builtin { location, .. }
+            | ReqxError::Filter { location, .. }
+            | ReqxError::File { location, .. }
+            | ReqxError::Extraction { location, .. }
+            | ReqxError::UnclosedInterpolation { location, .. }
+            | ReqxError::Transport { location, .. } => Some(location),
+            ReqxError::InvalidSelection { .. } => None,
+        }
+    }
+
+    /// The error message without its location.
+    pub fn message(&self) -> String {
+        match self {
+            ReqxError::Lex { message, .. }
+            | ReqxError::Parse { message, .. }
+            | ReqxError::Reference { message, .. }
+            | ReqxError::Builtin { message, .. }
+            | ReqxError::Filter { message, .. }
+            | ReqxError::File { message, .. }
+            | ReqxError::Transport { message, .. }
+            | ReqxError::InvalidSelection { message } => message.clone(),
+            ReqxError::UndefinedVariable { name, .. } => format!("Undefined variable: {}", name),
+            ReqxError::Extraction { name, message, .. } => {
+                format!("Cannot extract `@{}`: {}", name, message)
+            }
+            ReqxError::UnclosedInterpolation { text, .. } => {
+                format!("Unclosed variable interpolation: {}", text)
+            }
+        }
+    }
+
+    /// A hint on how to fix the error, if there is one.
+    pub fn help(&self) -> Option<String> {
+        match self {
+            ReqxError::Lex { help, .. }
+            | ReqxError::Parse { help, .. }
+            | ReqxError::Reference { help, .. }
+            | ReqxError::Builtin { help, .. }
+            | ReqxError::Filter { help, .. } => help.clone(),
+            ReqxError::UndefinedVariable {
+                suggestion: Some(suggestion),
+                ..
+            } => Some(format!("did you mean `{{{{{}}}}}`?", suggestion)),
+            ReqxError::UndefinedVariable { name, .. } => Some(format!(
+                "define it with `@{} = value` or extract it from an earlier response",
+                name
+            )),
+            ReqxError::Extraction { name, .. } => Some(format!(
+                "if the response may not contain it, mark it optional with `@{}? = ...`",
+                name
+            )),
+            ReqxError::UnclosedInterpolation { .. } => Some(
+                "close the placeholder with `}}`, or write `\\{{` for a literal `{{`".to_string(),
+            ),
+            ReqxError::File { .. }
+            | ReqxError::Transport { .. }
+            | ReqxError::InvalidSelection { .. } => None,
+        }
+    }
+
+    /// Attach the path of the `.reqx` file the error comes from.
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.path = Some(path.as_ref().to_path_buf());
+        }
+        self
+    }
+
+    /// Point the error at `span`, for errors in text that is not part of the
+    /// `.reqx` file, such as the contents of a `<@ template.json` body.
+    pub(crate) fn at(mut self, span: Span) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.span = span;
+        }
+        self
+    }
+}
+
+impl fmt::Display for ReqxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{}: {}", location, self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for ReqxError {}
@@ -0,0 +1,161 @@
+//! Evaluation of `@name = source` extractions against a response.
+
+use crate::ast::ExtractSource;
+use crate::client::HttpResponse;
+use crate::jsonpath;
+use crate::markup::{self, Markup};
+use serde_json::Value;
+
+/// The value `source` selects in `response`, or why there is none. JSON paths
+/// keep the type of what they select and `status` is a number; everything
+/// else is a string.
+pub(crate) fn extract(source: &ExtractSource, response: &HttpResponse) -> Result<Value, String> {
+    let text = match source {
+        ExtractSource::Status => return Ok(Value::from(response.status)),
+        ExtractSource::Header(name) => response
+            .header(name)
+            .map(str::to_string)
+            .ok_or_else(|| format!("the response has no `{}` header", name)),
+        ExtractSource::Cookie(name) => cookie(response, name)
+            .ok_or_else(|| format!("the response does not set a `{}` cookie", name)),
+        ExtractSource::JsonPath(path) => {
+            let json: Value =
+                serde_json::from_str(&response.text()).map_err(|_| not_json(response))?;
+            return jsonpath::select_match(&json, path)
+                .map_err(|e| e.message)?
+                .ok_or_else(|| format!("`{}` matched nothing in the response body", path));
+        }
+        ExtractSource::Regex(pattern) => {
+            let re = regex::Regex::new(pattern).map_err(|e| e.to_string())?;
+            let body = response.text();
+            let captures = re
+                .captures(&body)
+                .ok_or_else(|| "the pattern matched nothing in the response body".to_string())?;
+            // The first capture group if there is one, the whole match otherwise.
+            let m = captures.get(1).or_else(|| captures.get(0)).unwrap();
+            Ok(m.as_str().to_string())
+        }
+        ExtractSource::XPath(expr) => markup::xpath(expr, response)?
+            .ok_or_else(|| format!("`{}` matched nothing in the response body", expr)),
+        ExtractSource::Css {
+            selector,
+            attribute,
+        } => markup::css(selector, attribute.as_deref(), response)?
+            .ok_or_else(|| format!("`{}` matched nothing in the response body", selector)),
+    };
+    text.map(Value::String)
+}
+
+/// Why a JSON path cannot be used on `response`, pointing XML and HTML
+/// bodies at the extractors that can read them.
+fn not_json(response: &HttpResponse) -> String {
+    match Markup::of(response) {
+        Some(Markup::Xml) => "the response body is XML, not JSON; use `xpath` to extract from it",
+        Some(Markup::Html) => {
+            "the response body is HTML, not JSON; use `css` or `xpath` to extract from it"
+        }
+        None => "the response body is not valid JSON",
+    }
+    .to_string()
+}
+
+/// The value of cookie `name` from the `Set-Cookie` headers.
+fn cookie(response: &HttpResponse, name: &str) -> Option<String> {
+    response
+        .headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("set-cookie"))
+        .filter_map(|(_, v)| v.split(';').next()?.split_once('='))
+        .find(|(k, _)| k.trim() == name)
+        .map(|(_, v)| {
+            let v = v.trim();
+            v.strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(v)
+                .to_string()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 302,
+            status_is_success: false,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: vec![
+                ("location".to_string(), "/home".to_string()),
+                ("set-cookie".to_string(), "theme=dark; Path=/".to_string()),
+                (
+                    "set-cookie".to_string(),
+                    "session_id=\"s3cr3t\"; HttpOnly; Secure".to_string(),
+                ),
+            ],
+            body: r#"<form><input name="csrf" value="tok-42"></form>"#.into(),
+        }
+    }
+
+    #[test]
+    fn test_extract_sources() {
+        let r = response();
+        assert_eq!(extract(&ExtractSource::Status, &r).unwrap(), 302);
+        assert_eq!(
+            extract(&ExtractSource::Header("Location".to_string()), &r).unwrap(),
+            "/home"
+        );
+        assert_eq!(
+            extract(&ExtractSource::Cookie("session_id".to_string()), &r).unwrap(),
+            "s3cr3t"
+        );
+        assert_eq!(
+            extract(
+                &ExtractSource::Regex(r#"name="csrf" value="([^"]+)""#.to_string()),
+                &r
+            )
+            .unwrap(),
+            "tok-42"
+        );
+        assert_eq!(
+            extract(&ExtractSource::Regex(r"tok-\d+".to_string()), &r).unwrap(),
+            "tok-42"
+        );
+    }
+
+    #[test]
+    fn test_extract_failures() {
+        let r = response();
+        let err = |source| extract(&source, &r).unwrap_err();
+        assert_eq!(
+            err(ExtractSource::Header("ETag".to_string())),
+            "the response has no `ETag` header"
+        );
+        assert_eq!(
+            err(ExtractSource::Cookie("lang".to_string())),
+            "the response does not set a `lang` cookie"
+        );
+        assert_eq!(
+            err(ExtractSource::JsonPath("user.id".to_string())),
+            "the response body is not valid JSON"
+        );
+        assert_eq!(
+            err(ExtractSource::Regex("nope".to_string())),
+            "the pattern matched nothing in the response body"
+        );
+
+        let r = HttpResponse {
+            body: r#"{"items": []}"#.into(),
+            ..response()
+        };
+        assert_eq!(
+            extract(&ExtractSource::JsonPath("$.items[*].id".to_string()), &r).unwrap_err(),
+            "`$.items[*].id` matched nothing in the response body"
+        );
+        assert_eq!(
+            extract(&ExtractSource::JsonPath("$.items".to_string()), &r).unwrap(),
+            serde_json::json!([])
+        );
+    }
+}
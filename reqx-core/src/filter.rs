@@ -0,0 +1,313 @@
+//! Filters transform a value before it is interpolated:
+//! `{{user | urlencode}}`, `{{creds | base64}}`, `{{body | sha256 | hex}}`,
+//! `{{missing | default "x"}}`.
+//!
+//! Filters work on bytes, so `sha256` can feed its digest to `hex` or
+//! `base64`. Embedders can add their own with `Filters::register`.
+
+use crate::dynamic::split_args;
+use crate::interpreter::closest_name;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A filter function: takes the value and the filter's arguments, and returns
+/// the new value or a reason it cannot be applied.
+pub type FilterFn = dyn Fn(&[u8], &[String]) -> Result<Vec<u8>, String> + Send + Sync;
+
+/// The name of the filter that supplies a value for missing variables. It is
+/// part of the syntax and cannot be registered.
+const DEFAULT: &str = "default";
+
+/// Why a filter pipeline could not be applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilterError {
+    pub message: String,
+    pub help: Option<String>,
+}
+
+/// The filters available to `{{value | name}}` pipes. `Filters::default()`
+/// has the built-in ones: `urlencode`, `base64`, `sha256`, `hex`, `upper`
+/// and `lower`.
+#[derive(Clone)]
+pub struct Filters {
+    functions: BTreeMap<String, Arc<FilterFn>>,
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        let mut filters = Filters {
+            functions: BTreeMap::new(),
+        };
+        filters.register("urlencode", |value, args| {
+            no_args(args)?;
+            Ok(urlencode(value).into_bytes())
+        });
+        filters.register("base64", |value, args| {
+            no_args(args)?;
+            Ok(base64::engine::general_purpose::STANDARD
+                .encode(value)
+                .into_bytes())
+        });
+        filters.register("sha256", |value, args| {
+            no_args(args)?;
+            Ok(Sha256::digest(value).to_vec())
+        });
+        filters.register("hex", |value, args| {
+            no_args(args)?;
+            Ok(value
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+                .into_bytes())
+        });
+        filters.register("upper", |value, args| {
+            no_args(args)?;
+            Ok(text(value)?.to_uppercase().into_bytes())
+        });
+        filters.register("lower", |value, args| {
+            no_args(args)?;
+            Ok(text(value)?.to_lowercase().into_bytes())
+        });
+        filters
+    }
+}
+
+impl fmt::Debug for Filters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.functions.keys()).finish()
+    }
+}
+
+impl Filters {
+    /// Add a filter, replacing any filter with the same name.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is `default`, which is reserved.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        filter: impl Fn(&[u8], &[String]) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    ) {
+        let name = name.into();
+        assert!(name != DEFAULT, "the `default` filter cannot be replaced");
+        self.functions.insert(name, Arc::new(filter));
+    }
+
+    /// The names of the registered filters, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// Run `value` through `pipes`, the filters after the first `|`. A missing
+    /// `value` stays missing until a `default` filter supplies one.
+    pub(crate) fn apply(
+        &self,
+        value: Option<String>,
+        pipes: &[&str],
+    ) -> Result<Option<String>, FilterError> {
+        let mut value = value.map(String::into_bytes);
+        let mut last = None;
+        for pipe in pipes {
+            let (name, rest) = pipe
+                .split_once(char::is_whitespace)
+                .map_or((*pipe, ""), |(name, rest)| (name, rest.trim()));
+            if name.is_empty() {
+                return Err(FilterError {
+                    message: "a `|` must be followed by a filter name".to_string(),
+                    help: Some(self.help()),
+                });
+            }
+            let args = split_args(rest).map_err(|message| self.failed(name, message))?;
+
+            if name == DEFAULT {
+                let [fallback] = args.as_slice() else {
+                    return Err(self.failed(
+                        name,
+                        "it takes one value, as in `default \"x\"`".to_string(),
+                    ));
+                };
+                value = value.or_else(|| Some(fallback.clone().into_bytes()));
+                continue;
+            }
+
+            let Some(filter) = self.functions.get(name) else {
+                let names: Vec<String> = self.names().map(String::from).collect();
+                return Err(FilterError {
+                    message: format!("unknown filter `{}`", name),
+                    help: Some(match closest_name(name, names.iter()) {
+                        Some(suggestion) => format!("did you mean `{}`?", suggestion),
+                        None => self.help(),
+                    }),
+                });
+            };
+            if let Some(ref bytes) = value {
+                value = Some(filter(bytes, &args).map_err(|message| self.failed(name, message))?);
+                last = Some(name);
+            }
+        }
+
+        value
+            .map(|bytes| {
+                String::from_utf8(bytes).map_err(|_| FilterError {
+                    message: format!("the result of `{}` is not text", last.unwrap_or("")),
+                    help: Some("pipe it through `hex` or `base64`".to_string()),
+                })
+            })
+            .transpose()
+    }
+
+    /// An error for filter `name` failing with `message`.
+    fn failed(&self, name: &str, message: String) -> FilterError {
+        FilterError {
+            message: format!("cannot apply `{}`: {}", name, message),
+            help: None,
+        }
+    }
+
+    fn help(&self) -> String {
+        let names: Vec<String> = self
+            .names()
+            .chain([DEFAULT])
+            .map(|name| format!("`{}`", name))
+            .collect();
+        format!("filters are {}", names.join(", "))
+    }
+}
+
+/// Split a placeholder into what it interpolates and its filters:
+/// `user | urlencode` gives `user` and `["urlencode"]`. `||` and `|` inside
+/// quotes or brackets do not split, so JSONPath filters keep working.
+pub(crate) fn split_pipes(expr: &str) -> (&str, Vec<&str>) {
+    let bytes = expr.as_bytes();
+    let mut parts = Vec::new();
+    let (mut start, mut depth, mut quoted) = (0, 0usize, false);
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'"' => quoted = !quoted,
+            b'[' | b'(' if !quoted => depth += 1,
+            b']' | b')' if !quoted => depth = depth.saturating_sub(1),
+            b'|' if !quoted
+                && depth == 0
+                && bytes.get(i + 1) != Some(&b'|')
+                && (i == 0 || bytes[i - 1] != b'|') =>
+            {
+                parts.push(expr[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(expr[start..].trim());
+    let head = parts.remove(0);
+    (head, parts)
+}
+
+fn no_args(args: &[String]) -> Result<(), String> {
+    match args.is_empty() {
+        true => Ok(()),
+        false => Err("it takes no arguments".to_string()),
+    }
+}
+
+fn text(value: &[u8]) -> Result<&str, String> {
+    std::str::from_utf8(value).map_err(|_| "the value is not text".to_string())
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+pub(crate) fn urlencode(value: &[u8]) -> String {
+    value
+        .iter()
+        .map(|&b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(value: Option<&str>, expr: &str) -> Result<Option<String>, FilterError> {
+        let (_, pipes) = split_pipes(expr);
+        Filters::default().apply(value.map(String::from), &pipes)
+    }
+
+    #[test]
+    fn test_split_pipes() {
+        assert_eq!(split_pipes("user"), ("user", vec![]));
+        assert_eq!(
+            split_pipes(" body | sha256|hex "),
+            ("body", vec!["sha256", "hex"])
+        );
+        assert_eq!(
+            split_pipes(r#"x | default "a | b""#),
+            ("x", vec![r#"default "a | b""#])
+        );
+        assert_eq!(
+            split_pipes("login.response.body.$.items[?@.a || @.b]"),
+            ("login.response.body.$.items[?@.a || @.b]", vec![])
+        );
+    }
+
+    #[test]
+    fn test_builtin_filters() {
+        let ok = |value, expr| apply(Some(value), expr).unwrap().unwrap();
+        assert_eq!(ok("a b&c/é", "x | urlencode"), "a%20b%26c%2F%C3%A9");
+        assert_eq!(ok("user:pass", "x | base64"), "dXNlcjpwYXNz");
+        assert_eq!(
+            ok("abc", "x | sha256 | hex"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(ok("Jane", "x | upper"), "JANE");
+        assert_eq!(ok("Jane", "x | lower | default \"x\""), "jane");
+        assert_eq!(
+            apply(None, "x | default \"guest\" | upper").unwrap(),
+            Some("GUEST".to_string())
+        );
+        assert_eq!(apply(None, "x | upper").unwrap(), None);
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        let err = apply(Some("a"), "x | uper").unwrap_err();
+        assert_eq!(err.message, "unknown filter `uper`");
+        assert_eq!(err.help.unwrap(), "did you mean `upper`?");
+
+        let err = apply(Some("a"), "x | sha256").unwrap_err();
+        assert_eq!(err.message, "the result of `sha256` is not text");
+
+        let err = apply(Some("a"), "x | default").unwrap_err();
+        assert_eq!(
+            err.message,
+            "cannot apply `default`: it takes one value, as in `default \"x\"`"
+        );
+        assert!(apply(Some("a"), "x | upper 1").is_err());
+        assert!(apply(Some("a"), "x |").is_err());
+    }
+
+    #[test]
+    fn test_register_filter() {
+        let mut filters = Filters::default();
+        filters.register("repeat", |value, args| {
+            let times: usize = args
+                .first()
+                .and_then(|n| n.parse().ok())
+                .ok_or("it needs a count")?;
+            Ok(value.repeat(times))
+        });
+        assert_eq!(
+            filters
+                .apply(Some("ab".to_string()), &["repeat 3"])
+                .unwrap(),
+            Some("ababab".to_string())
+        );
+        assert!(filters.names().any(|name| name == "repeat"));
+    }
+}
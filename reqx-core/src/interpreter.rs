@@ -1,125 +1,815 @@
 /// Interpreter: resolves variables and executes HTTP requests.
-use crate::ast::{HttpMethod, Request, ReqxFile};
-use crate::client::HttpClient;
-use colored::Colorize;
-use std::collections::HashMap;
+use crate::assertion;
+use crate::ast::{Body, ExtractSource, HttpMethod, Param, Part, Request, ReqxFile};
+use crate::client::{HttpClient, HttpResponse};
+use crate::dynamic::Dynamic;
+use crate::error::{Location, ReqxError};
+use crate::extraction;
+use crate::filter::{self, Filters};
+use crate::jsonpath;
+use crate::multipart::{self, ResolvedPart};
+use crate::output;
+use crate::reference;
+use crate::report::{
+    AssertionResult, ExecutionReport, Extraction, RequestReport, ResolvedRequest, VariableOrigin,
+};
+use crate::reporter::{Reporter, SkipReason};
+use crate::span::Span;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-/// Execute all requests or a specific request in a `ReqxFile`.
-pub fn execute<C: HttpClient>(
+/// Which requests `execute` runs, and how.
+#[derive(Debug, Clone, Default)]
+pub struct ExecuteOptions {
+    /// Resolve requests without sending them.
+    pub dry_run: bool,
+    /// When a request fails with an error, report it with
+    /// `Reporter::request_failed` and carry on with the next requests, instead
+    /// of returning the error. Requests that reference the failed one fail too.
+    pub keep_going: bool,
+    /// Run only the request at this 1-based index.
+    pub request_index: Option<usize>,
+    /// Run only requests with this HTTP method (e.g. `POST`).
+    pub method_filter: Option<String>,
+    /// Run only requests whose `@name` matches one of these patterns.
+    /// `*` matches any run of characters and `?` a single one.
+    pub name_patterns: Vec<String>,
+    /// Seed for the random values of `{{$uuid}}`, `{{$randomInt}}` and
+    /// `{{$randomString}}`, so a run can be repeated exactly.
+    pub seed: Option<u64>,
+    /// Values for `{{$env.NAME}}` when the process environment does not set
+    /// `NAME`, such as the contents of a `.env` file.
+    pub env: HashMap<String, String>,
+    /// Variables from outside the file, such as a named environment, lowest
+    /// precedence first. The file's `@name = value` lines override them all.
+    pub environment: Vec<VariableLayer>,
+    /// Variables that override the file's own, such as values given on the
+    /// command line, lowest precedence first.
+    pub overrides: Vec<VariableLayer>,
+    /// The filters `{{value | name}}` can use. The default has the built-in
+    /// ones; embedders can register their own.
+    pub filters: Filters,
+    /// The directory `< path` bodies are relative to, normally the one holding
+    /// the `.reqx` file. Empty means the current directory.
+    pub base_dir: PathBuf,
+    /// Write every response body to this directory, in a file named after the
+    /// request: `<name>.json` for `@name`, or `request-<n>.json`. The
+    /// extension follows the `Content-Type`.
+    pub output_dir: Option<PathBuf>,
+    /// With `output_dir`, also write the status and headers of each response
+    /// to `<name>.headers`.
+    pub output_headers: bool,
+}
+
+/// A set of variables defined outside the `.reqx` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariableLayer {
+    /// Where the variables come from, e.g. `staging`. Reported as their origin.
+    pub name: String,
+    pub values: HashMap<String, Value>,
+    /// Whether the values are text as typed, such as `--var id=42`, rather
+    /// than JSON. Like the file's own variables, text that is valid JSON is
+    /// written as JSON in a JSON body.
+    pub untyped: bool,
+}
+
+/// Execute the requests of a `ReqxFile` selected by `options`.
+///
+/// Nothing is printed: progress is streamed to `reporter`, and the returned
+/// `ExecutionReport` describes every request that ran.
+///
+/// A request that references another one (`{{login.response.body.$.token}}`)
+/// runs it first if it has not run yet, even if it was not selected.
+pub fn execute<C: HttpClient, R: Reporter>(
     client: &C,
     file: &ReqxFile,
-    verbose: bool,
-    dry_run: bool,
-    request_index: Option<usize>,
-    method_filter: Option<String>,
-) -> Result<(), String> {
-    // Build variable map
-    let mut vars: HashMap<String, String> = HashMap::new();
+    reporter: &mut R,
+    options: &ExecuteOptions,
+) -> Result<ExecutionReport, ReqxError> {
+    // Build variable map, lowest precedence first
+    let mut vars: HashMap<String, Value> = HashMap::new();
+    let mut origins: HashMap<String, VariableOrigin> = HashMap::new();
+    let mut untyped = HashSet::new();
+    for layer in &options.environment {
+        for (name, value) in &layer.values {
+            vars.insert(name.clone(), value.clone());
+            origins.insert(
+                name.clone(),
+                VariableOrigin::Environment(layer.name.clone()),
+            );
+            if layer.untyped {
+                untyped.insert(name.clone());
+            } else {
+                untyped.remove(name);
+            }
+        }
+    }
     for var in &file.variables {
-        vars.insert(var.name.clone(), var.value.clone());
+        vars.insert(var.name.clone(), Value::String(var.value.clone()));
+        origins.insert(var.name.clone(), VariableOrigin::File);
+        untyped.insert(var.name.clone());
     }
-
-    if verbose {
-        println!("{}", "── Variables ──".dimmed());
-        for (k, v) in &vars {
-            println!("  {} = {}", k.cyan(), v);
+    for layer in &options.overrides {
+        for (name, value) in &layer.values {
+            vars.insert(name.clone(), value.clone());
+            origins.insert(name.clone(), VariableOrigin::Override(layer.name.clone()));
+            if layer.untyped {
+                untyped.insert(name.clone());
+            } else {
+                untyped.remove(name);
+            }
         }
-        println!();
     }
 
     let total = file.requests.len();
 
-    let mut requests_to_run: Vec<(usize, &Request)> = match request_index {
-        Some(idx) => {
-            if idx == 0 || idx > total {
-                return Err(format!(
+    if let Some(idx) = options.request_index {
+        if idx == 0 || idx > total {
+            return Err(ReqxError::InvalidSelection {
+                message: format!(
                     "Invalid request index: {}. The file has {} request(s).",
                     idx, total
-                ));
+                ),
+            });
+        }
+    }
+
+    let target_method =
+        match options.method_filter {
+            Some(ref m_str) => Some(m_str.parse::<HttpMethod>().ok().ok_or_else(|| {
+                ReqxError::InvalidSelection {
+                    message: format!("Invalid HTTP method filter: {}", m_str),
+                }
+            })?),
+            None => None,
+        };
+
+    check_name_patterns(file, &options.name_patterns)?;
+
+    reporter.run_started(&vars, &origins);
+
+    let mut run = Run {
+        client,
+        file,
+        reporter,
+        dry_run: options.dry_run,
+        vars,
+        untyped,
+        named: file
+            .requests
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| Some((r.name.as_deref()?, i)))
+            .collect(),
+        responses: HashMap::new(),
+        dynamic: Dynamic::new(options.seed, options.env.clone()),
+        filters: &options.filters,
+        base_dir: &options.base_dir,
+        output_dir: options.output_dir.as_deref(),
+        output_headers: options.output_headers,
+        ran: HashSet::new(),
+        stack: Vec::new(),
+        reports: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for (i, req) in file.requests.iter().enumerate() {
+        // Already run on demand by a request that references it.
+        if run.ran.contains(&i) {
+            continue;
+        }
+        let selected = options.request_index.is_none_or(|idx| idx - 1 == i)
+            && target_method.as_ref().is_none_or(|m| req.method == *m)
+            && (options.name_patterns.is_empty()
+                || req.name.as_deref().is_some_and(|name| {
+                    options
+                        .name_patterns
+                        .iter()
+                        .any(|pattern| glob_match(pattern, name))
+                }));
+        if !selected {
+            run.reporter
+                .request_skipped(i, req, SkipReason::NotSelected);
+            continue;
+        }
+
+        loop {
+            let error = match run.run_request(i) {
+                Ok(()) => break,
+                Err(e) if options.keep_going => e,
+                Err(e) => return Err(e),
+            };
+            // The error belongs to the innermost request being run, which may
+            // be one `i` references. Then `i` runs again without it.
+            let failed = run.stack.last().copied().unwrap_or(i);
+            run.stack.clear();
+            run.ran.insert(failed);
+            run.reporter
+                .request_failed(failed, &file.requests[failed], &error);
+            run.errors.push((failed, error));
+            if failed == i {
+                break;
             }
-            vec![(idx - 1, &file.requests[idx - 1])]
         }
-        None => file.requests.iter().enumerate().collect(),
+    }
+
+    let report = ExecutionReport {
+        total,
+        requests: run.reports,
+        errors: run.errors,
+        variables: run.vars,
     };
+    run.reporter.run_finished(&report);
 
-    if let Some(m_str) = method_filter {
-        let target_method = m_str
-            .parse::<HttpMethod>()
-            .ok()
-            .ok_or_else(|| format!("Invalid HTTP method filter: {}", m_str))?;
+    Ok(report)
+}
 
-        requests_to_run.retain(|(_, req)| req.method == target_method);
+/// The state of one `execute` call.
+struct Run<'a, C, R> {
+    client: &'a C,
+    file: &'a ReqxFile,
+    reporter: &'a mut R,
+    dry_run: bool,
+    vars: HashMap<String, Value>,
+    /// Variables still holding the text of a file `@name = value` line, which
+    /// has no JSON type of its own.
+    untyped: HashSet<String>,
+    /// The index of every named request.
+    named: HashMap<&'a str, usize>,
+    /// Responses of the named requests sent so far.
+    responses: HashMap<String, HttpResponse>,
+    dynamic: Dynamic,
+    filters: &'a Filters,
+    base_dir: &'a Path,
+    output_dir: Option<&'a Path>,
+    output_headers: bool,
+    /// Indexes of the requests that have run.
+    ran: HashSet<usize>,
+    /// The requests being run, innermost last. A request waiting on a reference
+    /// stays here while the referenced request runs, so cycles can be detected.
+    stack: Vec<usize>,
+    reports: Vec<RequestReport>,
+    /// Requests that failed with `keep_going`.
+    errors: Vec<(usize, ReqxError)>,
+}
 
-        if requests_to_run.is_empty() {
-            println!(
-                "{}",
-                format!("No requests matched the method filter: {}", m_str).dimmed()
-            );
-            return Ok(());
+impl<C: HttpClient, R: Reporter> Run<'_, C, R> {
+    /// Run request `index`, after the requests it references.
+    fn run_request(&mut self, index: usize) -> Result<(), ReqxError> {
+        let file = self.file;
+        let req = &file.requests[index];
+        self.stack.push(index);
+
+        for (dependency, span) in self.dependencies(req) {
+            if self.ran.contains(&dependency) {
+                continue;
+            }
+            if let Some(pos) = self.stack.iter().position(|&i| i == dependency) {
+                let cycle: Vec<&str> = self.stack[pos..]
+                    .iter()
+                    .chain([&dependency])
+                    .map(|&i| self.file.requests[i].name.as_deref().unwrap_or("?"))
+                    .collect();
+                return Err(ReqxError::Reference {
+                    message: format!("circular request reference: {}", cycle.join(" -> ")),
+                    help: Some("a request cannot depend on its own response".to_string()),
+                    location: Location::new(span),
+                });
+            }
+            self.run_request(dependency)?;
         }
+
+        self.reporter
+            .request_started(index, file.requests.len(), req);
+        let report = execute_request(
+            self.client,
+            index,
+            req,
+            &mut self.vars,
+            &Scope {
+                untyped: &self.untyped,
+                named: &self.named,
+                responses: &self.responses,
+                dynamic: &self.dynamic,
+                filters: self.filters,
+                base_dir: self.base_dir,
+                dry_run: self.dry_run,
+                output_dir: self.output_dir,
+                output_headers: self.output_headers,
+            },
+            self.reporter,
+        )?;
+        self.reporter.request_finished(&report);
+        for extraction in &report.extractions {
+            if extraction.value.is_some() {
+                self.untyped.remove(&extraction.name);
+            }
+        }
+
+        if let (Some(name), Some(response)) = (&req.name, &report.response) {
+            self.responses.insert(name.clone(), response.clone());
+        }
+        self.reports.push(report);
+        self.ran.insert(index);
+        self.stack.pop();
+        Ok(())
     }
 
-    for (i, req) in requests_to_run {
-        println!(
-            "{}",
-            format!("━━━ Request {}/{} ━━━", i + 1, total).bold().blue()
-        );
+    /// The named requests `req` references, with where each reference is.
+    /// Nothing is run on demand in a dry run, since nothing is sent.
+    fn dependencies(&self, req: &Request) -> Vec<(usize, Span)> {
+        if self.dry_run {
+            return Vec::new();
+        }
+        let mut texts = vec![(req.url.as_str(), req.url_span)];
+        // `<@ path` bodies and parts.
+        let mut templates = Vec::new();
+        for param in &req.query {
+            texts.push((&param.key, param.key_span));
+            texts.push((&param.value, param.value_span));
+        }
+        for h in &req.headers {
+            texts.push((&h.key, h.key_span));
+            texts.push((&h.value, h.value_span));
+        }
+        if let Some(output) = &req.output {
+            texts.push((&output.path, output.path_span));
+        }
+        match (&req.body, req.no_interpolate) {
+            (Some(Body::Text(body)), false) => {
+                texts.push((body, req.body_span.unwrap_or(req.span)));
+            }
+            (Some(Body::Graphql { query, variables }), false) => {
+                texts.push((query, req.body_span.unwrap_or(req.span)));
+                if let Some((variables, span)) = variables {
+                    texts.push((variables, *span));
+                }
+            }
+            (Some(Body::Form(fields)), false) => {
+                for field in fields {
+                    texts.push((&field.key, field.key_span));
+                    texts.push((&field.value, field.value_span));
+                }
+            }
+            (Some(Body::Multipart(parts)), false) => {
+                for part in parts {
+                    for h in &part.headers {
+                        texts.push((&h.key, h.key_span));
+                        texts.push((&h.value, h.value_span));
+                    }
+                    match &part.body {
+                        Body::Text(body) => texts.push((body, part.body_span)),
+                        Body::File {
+                            path,
+                            interpolate: true,
+                        } => templates.push((path, part.body_span)),
+                        _ => {}
+                    }
+                }
+            }
+            (
+                Some(Body::File {
+                    path,
+                    interpolate: true,
+                }),
+                false,
+            ) => templates.push((path, req.body_span.unwrap_or(req.span))),
+            _ => {}
+        }
+        for assertion in &req.assertions {
+            texts.push((&assertion.expected, assertion.expected_span));
+        }
 
-        if let Some(ref comment) = req.comment {
-            println!("{} {}", "▸".green(), comment.bold());
+        let mut dependencies = Vec::new();
+        for (text, span) in texts {
+            for (range, expr) in placeholders(text) {
+                if let Some(i) = self.dependency(expr) {
+                    dependencies.push((i, span.subspan(text, range)));
+                }
+            }
         }
+        // `<@` templates are read now and again when sent. An unreadable one
+        // is reported then. Their positions are not in the `.reqx` file, so
+        // references point at `<@ path`.
+        for (path, span) in templates {
+            let Ok(text) = fs::read_to_string(self.base_dir.join(path)) else {
+                continue;
+            };
+            for (_, expr) in placeholders(&text) {
+                if let Some(i) = self.dependency(expr) {
+                    dependencies.push((i, span));
+                }
+            }
+        }
+        dependencies
+    }
 
-        execute_request(client, req, &mut vars, verbose, dry_run)?;
-        println!();
+    /// The named request placeholder `expr` references, if any.
+    fn dependency(&self, expr: &str) -> Option<usize> {
+        let (expr, _) = filter::split_pipes(expr);
+        if self.vars.contains_key(expr) {
+            return None;
+        }
+        match reference::parse(expr) {
+            Some(Ok(reference)) => self.named.get(reference.request).copied(),
+            _ => None,
+        }
     }
+}
 
-    Ok(())
+/// What a request can refer to besides variables: request responses, built-in
+/// variables and filters in its placeholders, files for its body, and where
+/// its response is saved.
+struct Scope<'a> {
+    /// Variables holding untyped text from the file.
+    untyped: &'a HashSet<String>,
+    named: &'a HashMap<&'a str, usize>,
+    /// Responses of the named requests sent so far.
+    responses: &'a HashMap<String, HttpResponse>,
+    dynamic: &'a Dynamic,
+    filters: &'a Filters,
+    /// The directory `< path` bodies and `> path` outputs are relative to.
+    base_dir: &'a Path,
+    /// Requests are resolved but not sent.
+    dry_run: bool,
+    /// Where every response is saved, if anywhere.
+    output_dir: Option<&'a Path>,
+    output_headers: bool,
 }
 
-fn parse_variable_name(
-    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
-) -> Result<String, String> {
-    let mut var_name = String::new();
-    loop {
-        match chars.next() {
-            Some('}') if chars.peek() == Some(&'}') => {
-                chars.next(); // consume second '}'
-                break;
+impl Scope<'_> {
+    /// Resolve a request reference. `None` means the request is known but has
+    /// not been sent in a dry run, so the placeholder is kept as written.
+    fn resolve(
+        &self,
+        expr: &str,
+        vars: &HashMap<String, Value>,
+        span: Span,
+    ) -> Result<Option<Value>, ReqxError> {
+        let error = |message: String, help: Option<String>| ReqxError::Reference {
+            message,
+            help,
+            location: Location::new(span),
+        };
+        let reference = match reference::parse(expr) {
+            Some(Ok(reference)) => reference,
+            Some(Err(message)) => {
+                return Err(error(message, Some(reference::REFERENCE_HELP.to_string())));
             }
-            Some(c) => var_name.push(c),
             None => {
-                return Err(format!(
-                    "Unclosed variable interpolation: {{{{{}}}",
-                    var_name
-                ))
+                return Err(ReqxError::UndefinedVariable {
+                    name: expr.to_string(),
+                    suggestion: closest_name(expr, vars.keys()),
+                    location: Location::new(span),
+                });
             }
+        };
+
+        if !self.named.contains_key(reference.request) {
+            let names: Vec<String> = self.named.keys().map(|n| n.to_string()).collect();
+            let help = match closest_name(reference.request, names.iter()) {
+                Some(name) => format!("did you mean `{}`?", name),
+                None => format!(
+                    "name a request with `# @name {}` above its request line",
+                    reference.request
+                ),
+            };
+            return Err(error(
+                format!("no request is named `{}`", reference.request),
+                Some(help),
+            ));
+        }
+
+        match self.responses.get(reference.request) {
+            Some(response) => reference::resolve(&reference.part, response)
+                .map(Some)
+                .map_err(|reason| error(format!("cannot resolve `{}`: {}", expr, reason), None)),
+            None if self.dry_run => Ok(None),
+            // Requests are run before the ones that reference them, so this
+            // only happens for references that could not be found beforehand.
+            None => Err(error(
+                format!(
+                    "`{}` has not been sent, so `{}` cannot be resolved",
+                    reference.request, expr
+                ),
+                Some(format!(
+                    "run `{}` before this request, or extract the value from its response",
+                    reference.request
+                )),
+            )),
+        }
+    }
+}
+
+/// The `{{...}}` placeholders in `s`: their byte range and trimmed contents.
+/// Stops at an unclosed `{{`, which `interpolate` reports.
+fn placeholders(s: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while let Some(rel) = s[pos..].find("{{") {
+        let open = pos + rel;
+        if is_escaped(s, open) {
+            pos = open + 2;
+            continue;
         }
+        let Some(close_rel) = s[open + 2..].find("}}") else {
+            break;
+        };
+        let close = open + 2 + close_rel;
+        found.push((open..close + 2, s[open + 2..close].trim()));
+        pos = close + 2;
     }
-    Ok(var_name.trim().to_string())
+    found
 }
 
-/// Interpolate `{{var}}` placeholders in a string.
-fn interpolate(s: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+/// Every name pattern must match at least one request, so typos are not
+/// silently ignored.
+fn check_name_patterns(file: &ReqxFile, patterns: &[String]) -> Result<(), ReqxError> {
+    let names: Vec<String> = file
+        .requests
+        .iter()
+        .filter_map(|r| r.name.clone())
+        .collect();
+    for pattern in patterns {
+        if names.iter().any(|name| glob_match(pattern, name)) {
+            continue;
+        }
+        let message = if pattern.contains(['*', '?']) {
+            format!("No request name matches `{}`.", pattern)
+        } else {
+            match closest_name(pattern, names.iter()) {
+                Some(name) => format!(
+                    "No request is named `{}`. Did you mean `{}`?",
+                    pattern, name
+                ),
+                None => format!("No request is named `{}`.", pattern),
+            }
+        };
+        return Err(ReqxError::InvalidSelection { message });
+    }
+    Ok(())
+}
+
+/// Match `name` against a glob `pattern` where `*` matches any run of
+/// characters (including none) and `?` matches exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the last `*`: its position and the name position it matched up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character.
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Where interpolated text goes, which decides how values are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// Plain text, such as a URL or a header: values are written as text.
+    Text,
+    /// A JSON body: values are escaped inside string literals and written as
+    /// JSON outside them.
+    Json,
+    /// An output path: values are written as text, and must be plain file
+    /// names, so a value from a response cannot point outside the directory
+    /// the path names.
+    Path,
+}
+
+/// Interpolate `{{var}}` placeholders, `{{$uuid}}` built-in variables and
+/// `{{name.response...}}` request references in a string, through any
+/// `| filter`s that follow them. `\{{` stands for a literal `{{`.
+///
+/// `span` is where `s` appears in the source, so errors can point at the
+/// offending placeholder.
+fn interpolate(
+    s: &str,
+    span: Span,
+    vars: &HashMap<String, Value>,
+    scope: &Scope,
+    context: Context,
+) -> Result<String, ReqxError> {
     let mut result = String::new();
-    let mut chars = s.chars().peekable();
+    let mut pos = 0;
+    // Whether the text so far ends inside a JSON string literal.
+    let mut in_string = false;
 
-    while let Some(ch) = chars.next() {
-        if ch == '{' && chars.peek() == Some(&'{') {
-            chars.next(); // consume second '{'
-            let var_name = parse_variable_name(&mut chars)?;
+    while let Some(rel) = s[pos..].find("{{") {
+        let open = pos + rel;
+        // `\{{` is a literal `{{`.
+        let literal_end = if is_escaped(s, open) { open - 1 } else { open };
+        result.push_str(&s[pos..literal_end]);
+        if context == Context::Json {
+            in_string = ends_in_string(&s[pos..literal_end], in_string);
+        }
+        if literal_end != open {
+            result.push_str("{{");
+            pos = open + 2;
+            continue;
+        }
+
+        let Some(close_rel) = s[open + 2..].find("}}") else {
+            return Err(ReqxError::UnclosedInterpolation {
+                text: s[open..].to_string(),
+                location: Location::new(span.subspan(s, open..s.len())),
+            });
+        };
+        let close = open + 2 + close_rel;
+        let placeholder_span = span.subspan(s, open..close + 2);
+        let (var_name, pipes) = filter::split_pipes(&s[open + 2..close]);
 
-            let val = vars
-                .get(&var_name)
-                .ok_or_else(|| format!("Undefined variable: {}", var_name))?;
-            result.push_str(val);
+        // Built-in values, filter results and file variables are plain text.
+        let mut untyped = !pipes.is_empty();
+        let value = if let Some(expr) = var_name.strip_prefix('$') {
+            untyped = true;
+            scope
+                .dynamic
+                .evaluate(expr)
+                .map(|val| Some(Value::String(val)))
+                .map_err(|e| ReqxError::Builtin {
+                    message: e.message,
+                    help: Some(e.help),
+                    location: Location::new(placeholder_span),
+                })
         } else {
-            result.push(ch);
+            match vars.get(var_name) {
+                Some(val) => {
+                    untyped |= scope.untyped.contains(var_name);
+                    Ok(Some(val.clone()))
+                }
+                None => scope.resolve(var_name, vars, placeholder_span),
+            }
+        };
+        // A missing variable is only an error if no `default` filter supplies it.
+        let (value, missing) = match value {
+            Ok(Some(val)) => (Some(val), None),
+            // A reference to a request that has not been sent: keep it as written.
+            Ok(None) => {
+                result.push_str(&s[open..close + 2]);
+                pos = close + 2;
+                continue;
+            }
+            Err(e @ ReqxError::UndefinedVariable { .. }) if !pipes.is_empty() => (None, Some(e)),
+            Err(e) => return Err(e),
+        };
+        let value = match pipes.is_empty() {
+            true => value,
+            false => scope
+                .filters
+                .apply(value.as_ref().map(jsonpath::to_text), &pipes)
+                .map_err(|e| ReqxError::Filter {
+                    message: e.message,
+                    help: e.help,
+                    location: Location::new(placeholder_span),
+                })?
+                .map(Value::String),
+        };
+        let Some(value) = value else {
+            return Err(missing.expect("only a missing variable has no value"));
+        };
+        match context {
+            Context::Text => result.push_str(&jsonpath::to_text(&value)),
+            Context::Json => result.push_str(&to_json(&value, in_string, untyped)),
+            Context::Path => {
+                let text = jsonpath::to_text(&value);
+                if !is_file_name(&text) {
+                    return Err(ReqxError::File {
+                        message: format!(
+                            "`{}` is not a plain file name, so it cannot be used in an output path",
+                            text
+                        ),
+                        location: Location::new(placeholder_span),
+                    });
+                }
+                result.push_str(&text);
+            }
         }
+        pos = close + 2;
     }
+    result.push_str(&s[pos..]);
 
     Ok(result)
 }
 
+/// Whether `text` names a file without leaving its directory: no separators,
+/// drive, `.` or `..`.
+fn is_file_name(text: &str) -> bool {
+    !matches!(text, "" | "." | "..") && !text.contains(['/', '\\', ':', '\0'])
+}
+
+/// Whether the `{{` at `open` is escaped as `\{{`.
+fn is_escaped(s: &str, open: usize) -> bool {
+    s[..open].ends_with('\\')
+}
+
+/// Whether JSON text that starts inside a string literal if `in_string` ends
+/// inside one.
+fn ends_in_string(text: &str, mut in_string: bool) -> bool {
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ => {}
+        }
+    }
+    in_string
+}
+
+/// `value` as it is written into a JSON body: escaped inside a string literal.
+/// Outside one, strings are quoted, except `untyped` text that is valid JSON
+/// by itself, so `@id = 42` still gives a number and `@name = Jane` gives
+/// `"Jane"`, while an extracted `"42"` stays a string.
+fn to_json(value: &Value, in_string: bool, untyped: bool) -> String {
+    let text = jsonpath::to_text(value);
+    if in_string {
+        let quoted = Value::String(text).to_string();
+        return quoted[1..quoted.len() - 1].to_string();
+    }
+    match value {
+        Value::String(s) if !untyped || serde_json::from_str::<Value>(s).is_err() => {
+            value.to_string()
+        }
+        _ => text,
+    }
+}
+
+/// How to interpolate a request body or multipart part: as JSON if its
+/// `Content-Type` says so or, without one, if it looks like a JSON object or
+/// array.
+fn body_context(headers: &[(String, String)], body: &str) -> Context {
+    let json = match headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+    {
+        Some((_, v)) => v.to_ascii_lowercase().contains("json"),
+        // A JSON object cannot start with `{{`, but a placeholder does.
+        None => {
+            let body = body.trim_start();
+            body.starts_with(['{', '[']) && !body.starts_with("{{")
+        }
+    };
+    match json {
+        true => Context::Json,
+        false => Context::Text,
+    }
+}
+
+/// The candidate closest to `name`, if it is only a typo away.
+pub(crate) fn closest_name<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a String>,
+) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by(|(d1, c1), (d2, c2)| d1.cmp(d2).then_with(|| c1.cmp(c2)))
+        .map(|(_, c)| c.clone())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
 fn expand_url(url: &str) -> String {
     if url.starts_with(':') {
         format!("http://localhost{}", url)
@@ -128,179 +818,517 @@ fn expand_url(url: &str) -> String {
     }
 }
 
-fn execute_request<C: HttpClient>(
+/// The bytes to send for `body`, which spans `span`: its interpolated text, or
+/// the contents of its file.
+fn resolve_body(
+    body: &Body,
+    span: Span,
+    no_interpolate: bool,
+    headers: &[(String, String)],
+    vars: &HashMap<String, Value>,
+    scope: &Scope,
+) -> Result<Vec<u8>, ReqxError> {
+    let (text, template) = match body {
+        Body::Text(text) if no_interpolate => return Ok(text.clone().into_bytes()),
+        Body::Text(text) => (Cow::Borrowed(text.as_str()), false),
+        Body::File { path, interpolate } => {
+            let bytes = read_file(path, span, scope)?;
+            if !interpolate || no_interpolate {
+                return Ok(bytes);
+            }
+            let text = String::from_utf8(bytes).map_err(|_| ReqxError::File {
+                message: format!(
+                    "`{}` is not UTF-8 text, so it cannot be interpolated; send it as is with `< {}`",
+                    path, path
+                ),
+                location: Location::new(span),
+            })?;
+            (Cow::Owned(text), true)
+        }
+        Body::Multipart(parts) => {
+            return resolve_parts(parts, no_interpolate, vars, scope)
+                .map(|parts| multipart::encode(&parts, &boundary(headers)));
+        }
+        Body::Form(fields) => {
+            return encode_params(fields, no_interpolate, vars, scope).map(String::into_bytes);
+        }
+        Body::Graphql { query, variables } => {
+            return graphql_envelope(query, variables.as_ref(), span, no_interpolate, vars, scope);
+        }
+    };
+    let context = body_context(headers, &text);
+    match template {
+        false => interpolate(&text, span, vars, scope, context),
+        // The file's positions are not in the `.reqx` file: point at `<@ path`.
+        true => interpolate(&text, Span::default(), vars, scope, context).map_err(|e| e.at(span)),
+    }
+    .map(String::into_bytes)
+}
+
+/// The text of the check that fails a GraphQL request whose response has
+/// `errors`.
+const GRAPHQL_CHECK: &str = "no GraphQL errors";
+
+/// The JSON envelope of a GraphQL request: `{"query": ..., "variables": ...}`.
+/// The document spans from `span`, and its variables are interpolated as JSON.
+fn graphql_envelope(
+    query: &str,
+    variables: Option<&(String, Span)>,
+    span: Span,
+    no_interpolate: bool,
+    vars: &HashMap<String, Value>,
+    scope: &Scope,
+) -> Result<Vec<u8>, ReqxError> {
+    let resolve = |text: &str, span: Span, context: Context| match no_interpolate {
+        true => Ok(text.to_string()),
+        false => interpolate(text, span, vars, scope, context),
+    };
+    let mut envelope = serde_json::Map::new();
+    envelope.insert(
+        "query".to_string(),
+        Value::String(resolve(query, span, Context::Text)?),
+    );
+    if let Some((text, span)) = variables {
+        let text = resolve(text, *span, Context::Json)?;
+        let value = serde_json::from_str::<Value>(&text)
+            .ok()
+            .filter(Value::is_object)
+            .ok_or_else(|| ReqxError::Parse {
+                message: "the GraphQL variables are not a JSON object".to_string(),
+                help: Some(
+                    "write them as a JSON object after the document, such as `{\"id\": 1}`"
+                        .to_string(),
+                ),
+                location: Location::new(*span),
+            })?;
+        envelope.insert("variables".to_string(), value);
+    }
+    Ok(Value::Object(envelope).to_string().into_bytes())
+}
+
+/// The messages of the `errors` of a GraphQL response, if it has any.
+fn graphql_errors(response: &HttpResponse) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<Value>(&response.text()) else {
+        return Vec::new();
+    };
+    let Some(Value::Array(errors)) = json.get("errors") else {
+        return Vec::new();
+    };
+    errors
+        .iter()
+        .map(|error| match error.get("message") {
+            Some(Value::String(message)) => message.clone(),
+            _ => error.to_string(),
+        })
+        .collect()
+}
+
+/// `source` for a GraphQL response: dotted paths are looked up in `data`,
+/// unless they start at `data`, `errors` or `extensions`.
+fn graphql_source(source: &ExtractSource) -> Cow<'_, ExtractSource> {
+    match source {
+        ExtractSource::JsonPath(path)
+            if !path.starts_with('$')
+                && !["data", "errors", "extensions"]
+                    .iter()
+                    .any(|key| path.split(['.', '[']).next() == Some(key)) =>
+        {
+            Cow::Owned(ExtractSource::JsonPath(format!("data.{}", path)))
+        }
+        _ => Cow::Borrowed(source),
+    }
+}
+
+/// `params` as `key=value&...`, percent-encoded after interpolation.
+fn encode_params(
+    params: &[Param],
+    no_interpolate: bool,
+    vars: &HashMap<String, Value>,
+    scope: &Scope,
+) -> Result<String, ReqxError> {
+    let mut pairs = Vec::new();
+    for param in params {
+        let (key, value) = match no_interpolate {
+            true => (param.key.clone(), param.value.clone()),
+            false => (
+                interpolate(&param.key, param.key_span, vars, scope, Context::Text)?,
+                interpolate(&param.value, param.value_span, vars, scope, Context::Text)?,
+            ),
+        };
+        pairs.push(format!(
+            "{}={}",
+            filter::urlencode(key.as_bytes()),
+            filter::urlencode(value.as_bytes())
+        ));
+    }
+    Ok(pairs.join("&"))
+}
+
+/// `url` with the encoded `query` appended, before any `#fragment`.
+fn append_query(url: &str, query: &str) -> String {
+    if query.is_empty() {
+        return url.to_string();
+    }
+    let (base, fragment) = match url.find('#') {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    };
+    let separator = match base.find('?') {
+        None => "?",
+        Some(_) if base.ends_with(['?', '&']) => "",
+        Some(_) => "&",
+    };
+    format!("{}{}{}{}", base, separator, query, fragment)
+}
+
+/// Add a `Content-Type` header unless `headers` already has one.
+fn default_content_type(headers: &mut Vec<(String, String)>, content_type: &str) {
+    if !headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+    {
+        headers.push(("Content-Type".to_string(), content_type.to_string()));
+    }
+}
+
+fn read_file(path: &str, span: Span, scope: &Scope) -> Result<Vec<u8>, ReqxError> {
+    fs::read(scope.base_dir.join(path)).map_err(|e| ReqxError::File {
+        message: format!("cannot read `{}`: {}", path, e),
+        location: Location::new(span),
+    })
+}
+
+/// The headers and contents of multipart `parts`. `< file` parts are sent with
+/// the file's name.
+fn resolve_parts(
+    parts: &[Part],
+    no_interpolate: bool,
+    vars: &HashMap<String, Value>,
+    scope: &Scope,
+) -> Result<Vec<ResolvedPart>, ReqxError> {
+    let mut resolved = Vec::new();
+    for part in parts {
+        let mut headers = Vec::new();
+        for h in &part.headers {
+            headers.push(match no_interpolate {
+                true => (h.key.clone(), h.value.clone()),
+                false => (
+                    interpolate(&h.key, h.key_span, vars, scope, Context::Text)?,
+                    interpolate(&h.value, h.value_span, vars, scope, Context::Text)?,
+                ),
+            });
+        }
+        let filename = match &part.body {
+            Body::File { path, .. } => Some(
+                Path::new(path)
+                    .file_name()
+                    .map_or(path.clone(), |name| name.to_string_lossy().into_owned()),
+            ),
+            _ => None,
+        };
+        let content = resolve_body(
+            &part.body,
+            part.body_span,
+            no_interpolate,
+            &headers,
+            vars,
+            scope,
+        )?;
+        resolved.push(ResolvedPart {
+            name: part.name.clone(),
+            filename,
+            headers,
+            content,
+        });
+    }
+    Ok(resolved)
+}
+
+/// The boundary set in the multipart `Content-Type` of `headers`.
+fn boundary(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        .and_then(|(_, v)| v.split_once("boundary=").map(|(_, b)| b.to_string()))
+        .unwrap_or_default()
+}
+
+fn execute_request<C: HttpClient, R: Reporter>(
     client: &C,
+    index: usize,
     req: &Request,
-    vars: &mut HashMap<String, String>,
-    verbose: bool,
-    dry_run: bool,
-) -> Result<(), String> {
-    let interpolated_url = interpolate(&req.url, vars)?;
-    let url = expand_url(&interpolated_url);
+    vars: &mut HashMap<String, Value>,
+    scope: &Scope,
+    reporter: &mut R,
+) -> Result<RequestReport, ReqxError> {
+    let interpolated_url = interpolate(&req.url, req.url_span, vars, scope, Context::Text)?;
+    let query = encode_params(&req.query, false, vars, scope)?;
+    let url = append_query(&expand_url(&interpolated_url), &query);
 
     let mut resolved_headers = Vec::new();
     for h in &req.headers {
-        let key = interpolate(&h.key, vars)?;
-        let value = interpolate(&h.value, vars)?;
+        let key = interpolate(&h.key, h.key_span, vars, scope, Context::Text)?;
+        let value = interpolate(&h.value, h.value_span, vars, scope, Context::Text)?;
         resolved_headers.push((key, value));
     }
 
+    match req.body {
+        Some(Body::Multipart(_)) => {
+            let boundary = multipart::boundary();
+            resolved_headers.retain(|(k, _)| !k.eq_ignore_ascii_case("content-type"));
+            resolved_headers.push((
+                "Content-Type".to_string(),
+                multipart::content_type(&boundary),
+            ));
+        }
+        Some(Body::Form(_)) => {
+            default_content_type(&mut resolved_headers, "application/x-www-form-urlencoded")
+        }
+        Some(Body::Graphql { .. }) => {
+            default_content_type(&mut resolved_headers, "application/json")
+        }
+        _ => {}
+    }
+
     let body = match &req.body {
-        Some(b) => Some(interpolate(b, vars)?),
+        Some(body) => Some(resolve_body(
+            body,
+            req.body_span.unwrap_or(req.span),
+            req.no_interpolate,
+            &resolved_headers,
+            vars,
+            scope,
+        )?),
         None => None,
     };
 
-    // Display the request
-    let method_colored = match req.method {
-        HttpMethod::Get => "GET".green().bold(),
-        HttpMethod::Post => "POST".yellow().bold(),
-        HttpMethod::Put => "PUT".blue().bold(),
-        HttpMethod::Patch => "PATCH".magenta().bold(),
-        HttpMethod::Delete => "DELETE".red().bold(),
-        HttpMethod::Head => "HEAD".cyan().bold(),
-        HttpMethod::Options => "OPTIONS".white().bold(),
+    let mut report = RequestReport {
+        index,
+        comment: req.comment.clone(),
+        name: req.name.clone(),
+        request: ResolvedRequest {
+            method: req.method.clone(),
+            url,
+            headers: resolved_headers,
+            body,
+        },
+        response: None,
+        duration: None,
+        extractions: Vec::new(),
+        assertions: Vec::new(),
+        saved: Vec::new(),
     };
 
-    println!("{} {}", method_colored, url.underline());
+    reporter.request_resolved(&report.request);
 
-    if verbose {
-        for (k, v) in &resolved_headers {
-            println!("  {}: {}", k.dimmed(), v);
-        }
-        if let Some(ref b) = body {
-            println!("  {}", "Body:".dimmed());
-            // Try to pretty-print JSON bodies
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(b) {
-                let pretty = serde_json::to_string_pretty(&json).unwrap_or_else(|_| b.clone());
-                for line in pretty.lines() {
-                    println!("    {}", line);
-                }
-            } else {
-                for line in b.lines() {
-                    println!("    {}", line);
-                }
-            }
-        }
-    }
-
-    if dry_run {
-        println!("{}", "  (dry-run: request not sent)".dimmed().italic());
-        return Ok(());
+    if scope.dry_run {
+        reporter.request_skipped(index, req, SkipReason::DryRun);
+        return Ok(report);
     }
 
     // Actually execute the request
-    let response = client.execute(&req.method, &url, &resolved_headers, body.as_deref())?;
-
-    // Display response
-    let status = response.status;
-    let status_colored = if response.status_is_success {
-        format!("{}", status).green().bold()
-    } else if response.status_is_client_error {
-        format!("{}", status).yellow().bold()
-    } else if response.status_is_server_error {
-        format!("{}", status).red().bold()
-    } else {
-        format!("{}", status).white().bold()
-    };
-
-    println!("  {} {}", "Status:".dimmed(), status_colored);
+    let resolved = &report.request;
+    let started = Instant::now();
+    let response = client
+        .execute(
+            &resolved.method,
+            &resolved.url,
+            &resolved.headers,
+            resolved.body.as_deref(),
+        )
+        .map_err(|message| ReqxError::Transport {
+            message,
+            location: Location::new(req.span),
+        })?;
+    let duration = started.elapsed();
+    report.duration = Some(duration);
+    reporter.response_received(&response, duration);
 
-    if verbose {
-        println!("  {}", "Response Headers:".dimmed());
-        for (k, v) in &response.headers {
-            println!("    {}: {}", k.as_str().dimmed(), v.as_str());
+    // Saved before extractions, so a response that fails them can be inspected.
+    if let Some(dir) = scope.output_dir {
+        let stem = output::file_stem(req, index, scope.named);
+        let path = dir.join(format!("{}.{}", stem, output::extension(&response)));
+        save(
+            &path,
+            &response.body,
+            false,
+            req.span,
+            reporter,
+            &mut report,
+        )?;
+        if scope.output_headers {
+            let path = dir.join(format!("{}.headers", stem));
+            let headers = output::headers_text(&response);
+            save(
+                &path,
+                headers.as_bytes(),
+                false,
+                req.span,
+                reporter,
+                &mut report,
+            )?;
         }
     }
 
-    // Print response body
-    let resp_body = &response.body;
-
-    if !resp_body.is_empty() {
-        let json_val: Option<serde_json::Value> = serde_json::from_str(resp_body).ok();
-
-        // Try to pretty-print JSON
-        if let Some(ref json) = json_val {
-            let pretty = serde_json::to_string_pretty(json).unwrap_or_else(|_| resp_body.clone());
-            println!("  {}", "Response Body:".dimmed());
-            for line in pretty.lines() {
-                println!("    {}", line);
-            }
-        } else {
-            println!("  {}", "Response Body:".dimmed());
-            // Limit output for very large responses
-            let max_lines = 50;
-            let lines: Vec<&str> = resp_body.lines().collect();
-            for line in lines.iter().take(max_lines) {
-                println!("    {}", line);
-            }
-            if lines.len() > max_lines {
-                println!(
-                    "    {}",
-                    format!("... ({} more lines)", lines.len() - max_lines).dimmed()
-                );
-            }
-        }
+    let graphql = matches!(req.body, Some(Body::Graphql { .. }));
+    let graphql_errors = match graphql {
+        true => graphql_errors(&response),
+        false => Vec::new(),
+    };
 
-        // Handle extractions
-        if !req.extracts.is_empty() {
-            if let Some(json) = json_val {
-                if verbose {
-                    println!("  {}", "Extractions:".dimmed());
-                }
-                for extract in &req.extracts {
-                    let value = resolve_json_path(&json, &extract.value);
-                    if let Some(val_str) = value {
-                        vars.insert(extract.name.clone(), val_str.clone());
-                        if verbose {
-                            println!("    {} = {}", extract.name.cyan(), val_str);
-                        }
-                    } else if verbose {
-                        println!(
-                            "    {} = {}",
-                            extract.name.cyan(),
-                            "null (path not found)".red()
-                        );
-                    }
-                }
-            } else if verbose {
-                println!(
-                    "  {}",
-                    "Extractions skipped: response body is not valid JSON".red()
-                );
+    // Handle extractions
+    for extract in &req.extracts {
+        let source = match graphql {
+            true => graphql_source(&extract.source),
+            false => Cow::Borrowed(&extract.source),
+        };
+        let value = match extraction::extract(&source, &response) {
+            Ok(value) => Some(value),
+            Err(_) if extract.optional => None,
+            Err(message) => {
+                let message = match graphql_errors.is_empty() {
+                    true => message,
+                    false => format!(
+                        "{} (GraphQL errors: {})",
+                        message,
+                        graphql_errors.join("; ")
+                    ),
+                };
+                return Err(ReqxError::Extraction {
+                    name: extract.name.clone(),
+                    message,
+                    location: Location::new(extract.value_span),
+                });
             }
+        };
+        if let Some(ref val) = value {
+            vars.insert(extract.name.clone(), val.clone());
         }
+        let extraction = Extraction {
+            name: extract.name.clone(),
+            source: extract.text.clone(),
+            value,
+        };
+        reporter.extraction_performed(&extraction);
+        report.extractions.push(extraction);
     }
 
-    Ok(())
-}
+    // Check assertions, after extractions so they can use freshly extracted values
+    for assertion in &req.assertions {
+        let expected = interpolate(
+            &assertion.expected,
+            assertion.expected_span,
+            vars,
+            scope,
+            Context::Text,
+        )?;
+        let result = assertion::evaluate(assertion, &expected, &response, duration);
+        reporter.assertion_evaluated(&result);
+        report.assertions.push(result);
+    }
 
-fn resolve_json_path(json: &serde_json::Value, path: &str) -> Option<String> {
-    let mut current = json;
-    for part in path.split('.') {
-        if part.is_empty() {
-            continue;
-        }
-        current = current.get(part)?;
+    // A GraphQL response with errors fails, whatever its status.
+    if !graphql_errors.is_empty() {
+        let result = AssertionResult {
+            text: GRAPHQL_CHECK.to_string(),
+            line: req.line,
+            expected: "no errors".to_string(),
+            actual: Some(graphql_errors.join("; ")),
+            passed: false,
+            message: Some(format!(
+                "the response has {} GraphQL error(s): {}",
+                graphql_errors.len(),
+                graphql_errors.join("; ")
+            )),
+        };
+        reporter.assertion_evaluated(&result);
+        report.assertions.push(result);
     }
 
-    match current {
-        serde_json::Value::String(s) => Some(s.clone()),
-        serde_json::Value::Number(n) => Some(n.to_string()),
-        serde_json::Value::Bool(b) => Some(b.to_string()),
-        serde_json::Value::Null => Some("null".to_string()),
-        _ => Some(current.to_string()),
+    // After extractions, so the path can use extracted values.
+    if let Some(out) = &req.output {
+        let path = interpolate(&out.path, out.path_span, vars, scope, Context::Path)?;
+        let path = scope.base_dir.join(path);
+        save(
+            &path,
+            &response.body,
+            out.append,
+            out.path_span,
+            reporter,
+            &mut report,
+        )?;
     }
+
+    report.response = Some(response);
+    Ok(report)
+}
+
+/// Write `contents` to `path` and record it in `report`.
+fn save<R: Reporter>(
+    path: &Path,
+    contents: &[u8],
+    append: bool,
+    span: Span,
+    reporter: &mut R,
+    report: &mut RequestReport,
+) -> Result<(), ReqxError> {
+    output::write(path, contents, append).map_err(|e| ReqxError::File {
+        message: format!("cannot write `{}`: {}", path.display(), e),
+        location: Location::new(span),
+    })?;
+    reporter.response_saved(path);
+    report.saved.push(path.to_path_buf());
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Interpolate text with no named requests in scope.
+    fn interpolate_vars(
+        s: &str,
+        span: Span,
+        vars: &HashMap<String, String>,
+    ) -> Result<String, ReqxError> {
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        interpolate_in(s, span, &vars, Context::Text)
+    }
+
+    fn interpolate_in(
+        s: &str,
+        span: Span,
+        vars: &HashMap<String, Value>,
+        context: Context,
+    ) -> Result<String, ReqxError> {
+        // String values stand for file variables.
+        let untyped = vars
+            .iter()
+            .filter(|(_, v)| v.is_string())
+            .map(|(k, _)| k.clone())
+            .collect();
+        let scope = Scope {
+            untyped: &untyped,
+            named: &HashMap::new(),
+            responses: &HashMap::new(),
+            dynamic: &Dynamic::new(Some(0), HashMap::new()),
+            filters: &Filters::default(),
+            base_dir: Path::new(""),
+            dry_run: false,
+            output_dir: None,
+            output_headers: false,
+        };
+        interpolate(s, span, vars, &scope, context)
+    }
+
     #[test]
     fn test_interpolate_basic() {
         let mut vars = HashMap::new();
         vars.insert("name".to_string(), "world".to_string());
-        let result = interpolate("hello {{name}}!", &vars).unwrap();
+        let result = interpolate_vars("hello {{name}}!", Span::default(), &vars).unwrap();
         assert_eq!(result, "hello world!");
     }
 
@@ -309,24 +1337,251 @@ mod tests {
         let mut vars = HashMap::new();
         vars.insert("base".to_string(), "https://api.example.com".to_string());
         vars.insert("version".to_string(), "v2".to_string());
-        let result = interpolate("{{base}}/{{version}}/users", &vars).unwrap();
+        let result =
+            interpolate_vars("{{base}}/{{version}}/users", Span::default(), &vars).unwrap();
         assert_eq!(result, "https://api.example.com/v2/users");
     }
 
+    #[test]
+    fn test_interpolate_builtin_variables() {
+        let mut vars = HashMap::new();
+        vars.insert("uuid".to_string(), "mine".to_string());
+        let result = interpolate_vars(
+            "{{uuid}} {{ $randomInt 5 5 }} {{$uuid}}",
+            Span::default(),
+            &vars,
+        )
+        .unwrap();
+        assert!(result.starts_with("mine 5 "));
+        assert_eq!(result.len(), "mine 5 ".len() + 36);
+
+        let input = "x {{$uid}}";
+        let span = Span {
+            start: 0,
+            end: input.len(),
+            line: 1,
+            column: 1,
+        };
+        let err = interpolate_vars(input, span, &vars).unwrap_err();
+        match err {
+            ReqxError::Builtin {
+                ref message,
+                ref location,
+                ..
+            } => {
+                assert_eq!(message, "unknown built-in variable `$uid`");
+                assert_eq!(&input[location.span.start..location.span.end], "{{$uid}}");
+            }
+            ref other => panic!("expected a built-in variable error, got {:?}", other),
+        }
+        assert_eq!(err.help().unwrap(), "did you mean `{{$uuid}}`?");
+    }
+
+    #[test]
+    fn test_interpolate_filters() {
+        let mut vars = HashMap::new();
+        vars.insert("creds".to_string(), "user:pass".to_string());
+        let result = interpolate_vars(
+            "Basic {{ creds | base64 }} {{role | default \"guest\" | upper}}",
+            Span::default(),
+            &vars,
+        )
+        .unwrap();
+        assert_eq!(result, "Basic dXNlcjpwYXNz GUEST");
+
+        let err = interpolate_vars("{{role | upper}}", Span::default(), &vars).unwrap_err();
+        assert!(matches!(err, ReqxError::UndefinedVariable { ref name, .. } if name == "role"));
+
+        let input = "x {{creds | base46}}";
+        let span = Span {
+            start: 0,
+            end: input.len(),
+            line: 1,
+            column: 1,
+        };
+        match interpolate_vars(input, span, &vars) {
+            Err(ReqxError::Filter {
+                message, location, ..
+            }) => {
+                assert_eq!(message, "unknown filter `base46`");
+                assert_eq!(
+                    &input[location.span.start..location.span.end],
+                    "{{creds | base46}}"
+                );
+            }
+            other => panic!("expected a filter error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_interpolate_json_body() {
+        let vars: HashMap<String, Value> = [
+            ("id", Value::from("42")),
+            ("name", Value::from(r#"Jane "JD" Doe"#)),
+            ("ids", serde_json::json!([1, 2])),
+            ("user", serde_json::json!({"active": true})),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        let body = r#"{"id": {{id}}, "name": "{{name}}", "alias": {{name}}, "ids": {{ids}}, "note": "ids {{ids}} \"{{user}}\""}"#;
+        let result = interpolate_in(body, Span::default(), &vars, Context::Json).unwrap();
+        let json: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["id"], 42);
+        assert_eq!(json["name"], r#"Jane "JD" Doe"#);
+        assert_eq!(json["alias"], r#"Jane "JD" Doe"#);
+        assert_eq!(json["ids"], serde_json::json!([1, 2]));
+        assert_eq!(json["note"], r#"ids [1,2] "{"active":true}""#);
+
+        let text = interpolate_in("/users/{{ids}}", Span::default(), &vars, Context::Text);
+        assert_eq!(text.unwrap(), "/users/[1,2]");
+    }
+
+    #[test]
+    fn test_interpolate_output_path() {
+        let vars: HashMap<String, Value> = [
+            ("id", Value::from(42)),
+            ("up", Value::from("../../.bashrc")),
+            ("root", Value::from("/etc/passwd")),
+            ("parent", Value::from("..")),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let path = |s| interpolate_in(s, Span::default(), &vars, Context::Path);
+        assert_eq!(
+            path("../out/user-{{id}}.json").unwrap(),
+            "../out/user-42.json"
+        );
+        for s in ["./{{up}}", "{{root}}", "./{{parent}}/x.json"] {
+            assert!(matches!(path(s), Err(ReqxError::File { .. })), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_append_query() {
+        assert_eq!(append_query("http://a.com/s", "q=1"), "http://a.com/s?q=1");
+        assert_eq!(
+            append_query("http://a.com/s?x=0", "q=1"),
+            "http://a.com/s?x=0&q=1"
+        );
+        assert_eq!(append_query("http://a.com/s?", "q=1"), "http://a.com/s?q=1");
+        assert_eq!(
+            append_query("http://a.com/s#top", "q=1"),
+            "http://a.com/s?q=1#top"
+        );
+        assert_eq!(append_query("http://a.com/s", ""), "http://a.com/s");
+    }
+
+    #[test]
+    fn test_body_context() {
+        let json = [("content-type".to_string(), "application/json".to_string())];
+        let form = [(
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        )];
+        assert_eq!(body_context(&json, "x"), Context::Json);
+        assert_eq!(body_context(&form, "{}"), Context::Text);
+        assert_eq!(body_context(&[], "  [1]"), Context::Json);
+        assert_eq!(body_context(&[], "a=1"), Context::Text);
+        assert_eq!(body_context(&[], "{{payload}}"), Context::Text);
+    }
+
+    #[test]
+    fn test_interpolate_escaped_braces() {
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "world".to_string());
+        let result = interpolate_vars(
+            r"Hello \{{name}}, {{name}}! \{{#each items}}{{this}}",
+            Span::default(),
+            &vars,
+        );
+        assert!(result.is_err());
+        let result = interpolate_vars(
+            r"Hello \{{name}}, {{name}}! \{{#each items}}\{{this}}",
+            Span::default(),
+            &vars,
+        )
+        .unwrap();
+        assert_eq!(result, "Hello {{name}}, world! {{#each items}}{{this}}");
+        assert_eq!(placeholders(r"\{{a}} {{b}}"), vec![(7..12, "b")]);
+    }
+
     #[test]
     fn test_interpolate_undefined_var() {
         let vars = HashMap::new();
-        let result = interpolate("hello {{missing}}", &vars);
+        let result = interpolate_vars("hello {{missing}}", Span::default(), &vars);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_interpolate_error_spans() {
+        let span = Span {
+            start: 100,
+            end: 130,
+            line: 4,
+            column: 5,
+        };
+        let vars = HashMap::new();
+        match interpolate_vars("https://{{ host }}/users", span, &vars) {
+            Err(ReqxError::UndefinedVariable { name, location, .. }) => {
+                assert_eq!(name, "host");
+                assert_eq!(location.span.start, 108);
+                assert_eq!(location.span.end, 118);
+                assert_eq!(location.span.column, 13);
+            }
+            other => panic!("expected an undefined variable error, got {:?}", other),
+        }
+        match interpolate_vars("a\n{{oops", span, &vars) {
+            Err(ReqxError::UnclosedInterpolation { text, location }) => {
+                assert_eq!(text, "{{oops");
+                assert_eq!(location.span.line, 5);
+                assert_eq!(location.span.column, 1);
+            }
+            other => panic!("expected an unclosed interpolation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_undefined_variable_suggestion() {
+        let mut vars = HashMap::new();
+        vars.insert("base_url".to_string(), "https://api.com".to_string());
+        vars.insert("token".to_string(), "abc".to_string());
+
+        let err = interpolate_vars("{{base_ur}}/users", Span::default(), &vars).unwrap_err();
+        assert_eq!(err.help().unwrap(), "did you mean `{{base_url}}`?");
+
+        let err = interpolate_vars("{{completely_different}}", Span::default(), &vars).unwrap_err();
+        assert!(matches!(
+            err,
+            ReqxError::UndefinedVariable {
+                suggestion: None,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_interpolate_no_vars() {
         let vars = HashMap::new();
-        let result = interpolate("no interpolation here", &vars).unwrap();
+        let result = interpolate_vars("no interpolation here", Span::default(), &vars).unwrap();
         assert_eq!(result, "no interpolation here");
     }
 
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("login", "login"));
+        assert!(!glob_match("login", "login2"));
+        assert!(glob_match("user-*", "user-create"));
+        assert!(glob_match("user-*", "user-"));
+        assert!(glob_match("*-delete", "user-delete"));
+        assert!(glob_match("u?er*e", "user-delete"));
+        assert!(glob_match("*a*b*", "xxaybzz"));
+        assert!(!glob_match("*a*b", "xxaybzz"));
+        assert!(!glob_match("user-?", "user-"));
+    }
+
     #[test]
     fn test_expand_url_localhost_shorthand() {
         assert_eq!(expand_url(":3000"), "http://localhost:3000");
@@ -0,0 +1,244 @@
+//! JSONPath (RFC 9535) queries, used by extractions, `body.<path>` assertions
+//! and `{{name.response.body.<path>}}` references.
+//!
+//! Paths that do not start with `$` use the older dotted syntax (`user.id`,
+//! `items.0.id`) and are rewritten to JSONPath before parsing.
+
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// A parsed path.
+#[derive(Debug, Clone)]
+pub(crate) struct Query {
+    path: JsonPath,
+    /// Whether the path can select at most one node (only names and indices).
+    singular: bool,
+}
+
+/// Why a path could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct QueryError {
+    pub message: String,
+    /// Byte offset of the problem in the path as written, when it is known.
+    pub position: Option<usize>,
+}
+
+pub(crate) const JSONPATH_HELP: &str = "use JSONPath such as `$.items[0].id`, `$.users[*].name` or `$.users[?@.role == 'admin'].id`, or a dotted path such as `user.id`";
+
+impl Query {
+    pub fn parse(path: &str) -> Result<Self, QueryError> {
+        let (source, legacy) = match path.strip_prefix('$') {
+            Some(_) => (path.to_string(), false),
+            None => (normalize_legacy(path), true),
+        };
+        let parsed = JsonPath::parse(&source).map_err(|e| QueryError {
+            message: format!("invalid JSONPath `{}`: {}", path, e.message()),
+            // Positions in a rewritten path do not match what the user wrote.
+            position: (!legacy).then(|| e.position()),
+        })?;
+        Ok(Self {
+            path: parsed,
+            singular: is_singular(&source),
+        })
+    }
+
+    /// The selected value. A singular path gives the node it selects, if any;
+    /// any other path gives an array of every match, possibly empty (see
+    /// [`select_match`] for treating no match as missing).
+    pub fn select(&self, json: &Value) -> Option<Value> {
+        let nodes = self.path.query(json);
+        if self.singular {
+            nodes.first().cloned()
+        } else {
+            Some(Value::Array(nodes.into_iter().cloned().collect()))
+        }
+    }
+}
+
+/// Evaluate `path` against `json`.
+pub(crate) fn select(json: &Value, path: &str) -> Result<Option<Value>, QueryError> {
+    Ok(Query::parse(path)?.select(json))
+}
+
+/// Like [`select`], but a path that matches nothing gives `None` even when it
+/// would otherwise select an empty array of matches.
+pub(crate) fn select_match(json: &Value, path: &str) -> Result<Option<Value>, QueryError> {
+    let query = Query::parse(path)?;
+    if query.path.query(json).is_empty() {
+        return Ok(None);
+    }
+    Ok(query.select(json))
+}
+
+/// Evaluate `path` against `json` and render the result as text: strings
+/// without their quotes, anything else as JSON.
+pub(crate) fn select_text(json: &Value, path: &str) -> Result<Option<String>, QueryError> {
+    Ok(select(json, path)?.map(|value| to_text(&value)))
+}
+
+/// A value as text: strings without their quotes, anything else as JSON.
+pub fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Rewrite a dotted path (`items.0.id`, `data.user-name`) as JSONPath.
+fn normalize_legacy(path: &str) -> String {
+    let mut out = String::from("$");
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        // Keep any bracket selectors written after the name: `items[0]`.
+        let (name, brackets) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) {
+            out.push_str(&format!("[{}]", name));
+        } else if is_shorthand_name(name) {
+            out.push('.');
+            out.push_str(name);
+        } else if !name.is_empty() {
+            out.push_str(&format!(
+                "['{}']",
+                name.replace('\\', "\\\\").replace('\'', "\\'")
+            ));
+        }
+        out.push_str(brackets);
+    }
+    out
+}
+
+/// Names that RFC 9535 allows after a `.` without brackets.
+fn is_shorthand_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || !c.is_ascii())
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || !c.is_ascii())
+}
+
+/// Whether `path` (valid JSONPath) only uses name and index selectors, so it
+/// selects at most one node.
+fn is_singular(path: &str) -> bool {
+    let mut chars = path[1..].chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                // `..` is descendant search and `.*` a wildcard.
+                if matches!(chars.peek(), Some('.' | '*')) {
+                    return false;
+                }
+                while chars.next_if(|&c| c != '.' && c != '[').is_some() {}
+            }
+            '[' => {
+                let mut quote = None;
+                let mut escaped = false;
+                for c in chars.by_ref() {
+                    if let Some(q) = quote {
+                        if escaped {
+                            escaped = false;
+                        } else if c == '\\' {
+                            escaped = true;
+                        } else if c == q {
+                            quote = None;
+                        }
+                        continue;
+                    }
+                    match c {
+                        '\'' | '"' => quote = Some(c),
+                        ']' => break,
+                        // Wildcards, filters, slices and unions.
+                        '*' | '?' | ':' | ',' => return false,
+                        _ => {}
+                    }
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "token": "abc",
+            "user": {"id": 7, "user-name": "ada"},
+            "items": [{"id": 1}, {"id": 2}, {"id": 3}],
+            "users": [
+                {"name": "ada", "role": "admin"},
+                {"name": "bob", "role": "user"},
+                {"name": "eve", "role": "admin"}
+            ]
+        })
+    }
+
+    fn select(path: &str) -> Option<Value> {
+        Query::parse(path).unwrap().select(&doc())
+    }
+
+    #[test]
+    fn test_singular_paths() {
+        assert_eq!(select("$.token"), Some(json!("abc")));
+        assert_eq!(select("$.items[1].id"), Some(json!(2)));
+        assert_eq!(select("$.items[-1].id"), Some(json!(3)));
+        assert_eq!(select("$['user']['user-name']"), Some(json!("ada")));
+        assert_eq!(select("$.missing"), None);
+        assert_eq!(select("$"), Some(doc()));
+    }
+
+    #[test]
+    fn test_legacy_paths() {
+        assert_eq!(select("token"), Some(json!("abc")));
+        assert_eq!(select("user.id"), Some(json!(7)));
+        assert_eq!(select("items.0.id"), Some(json!(1)));
+        assert_eq!(select("items[2].id"), Some(json!(3)));
+        assert_eq!(select("user.user-name"), Some(json!("ada")));
+    }
+
+    #[test]
+    fn test_multi_match_paths() {
+        assert_eq!(select("$.items[*].id"), Some(json!([1, 2, 3])));
+        assert_eq!(select("$.items[0:2].id"), Some(json!([1, 2])));
+        assert_eq!(select("$..id"), Some(json!([1, 2, 3, 7])));
+        assert_eq!(
+            select("$.users[?(@.role=='admin')].name"),
+            Some(json!(["ada", "eve"]))
+        );
+        assert_eq!(select("$.users[?@.role == 'root'].name"), Some(json!([])));
+        assert_eq!(select("$.items[0,1].id"), Some(json!([1, 2])));
+        assert_eq!(select("$['a,b']"), None);
+    }
+
+    #[test]
+    fn test_select_match() {
+        let json = json!({"items": [], "empty": []});
+        assert_eq!(select_match(&json, "$.items[*].id").unwrap(), None);
+        assert_eq!(select_match(&json, "$.empty").unwrap(), Some(json!([])));
+        assert_eq!(
+            select_match(&doc(), "$.items[*].id").unwrap(),
+            Some(json!([1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        let err = Query::parse("$.items[?@.id ==]").unwrap_err();
+        assert!(err
+            .message
+            .starts_with("invalid JSONPath `$.items[?@.id ==]`"));
+        assert!(err.position.is_some());
+        assert!(Query::parse("items[").unwrap_err().position.is_none());
+    }
+
+    #[test]
+    fn test_select_text() {
+        assert_eq!(select_text(&doc(), "token").unwrap().unwrap(), "abc");
+        assert_eq!(
+            select_text(&doc(), "$.items[*].id").unwrap().unwrap(),
+            "[1,2,3]"
+        );
+    }
+}
@@ -1,10 +1,15 @@
 //! Lexer (tokenizer) for `.reqx` files.
 
+use crate::error::{Location, ReqxError};
+use crate::span::Span;
+
 /// Token types produced by the lexer.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     /// A comment line: `# some comment`
     Comment(String),
+    /// A directive comment: `# @name login`
+    Directive { name: String, value: String },
     /// Request separator: `###`
     Separator,
     /// Variable definition: `@name = value`
@@ -17,18 +22,84 @@ pub enum Token {
     Header { key: String, value: String },
     /// A body line (plain text or JSON)
     BodyLine(String),
+    /// A response assertion: `? status == 200` (text after the `?`)
+    Assertion(String),
+    /// An output redirect after a request: `> ./out.json` overwrites the file
+    /// with the response body, `>> ./log.json` appends to it
+    Output { path: String, append: bool },
+    /// An indented query parameter below the URL: `?page=2` or `&q=rust`
+    /// (text after the `?` or `&`)
+    QueryParam(String),
     /// An empty line
     BlankLine,
 }
 
-/// A token with its source line number.
+impl Token {
+    /// A short human-readable description, for error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Comment(_) => "a comment".to_string(),
+            Token::Directive { name, .. } => format!("directive `@{}`", name),
+            Token::Separator => "`###`".to_string(),
+            Token::Variable { name, .. } => format!("variable `@{}`", name),
+            Token::Method(m) => format!("method `{}`", m),
+            Token::Url(u) => format!("URL `{}`", u),
+            Token::Header { key, .. } => format!("header `{}`", key),
+            Token::BodyLine(_) => "body text".to_string(),
+            Token::Assertion(_) => "an assertion".to_string(),
+            Token::QueryParam(_) => "a query parameter".to_string(),
+            Token::Output { .. } => "an output redirect".to_string(),
+            Token::BlankLine => "a blank line".to_string(),
+        }
+    }
+}
+
+/// A token with its source position.
 #[derive(Debug, Clone)]
 pub struct LocatedToken {
     pub token: Token,
     pub line: usize,
+    /// The token's text in the source: the trimmed line for most tokens,
+    /// just the method word or URL for request lines, the raw line for body lines.
+    pub span: Span,
+    /// The value part of `Variable`, `Header` and `Directive` tokens, the
+    /// text of `Assertion` and `QueryParam` tokens, and the path of `Output`
+    /// tokens.
+    pub value_span: Option<Span>,
+}
+
+/// The methods a request line can start with. `GRAPHQL` is sent as `POST`.
+const HTTP_METHODS: [&str; 8] = [
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "GRAPHQL",
+];
+
+/// A single line of input and where it starts in the source.
+struct SourceLine<'a> {
+    raw: &'a str,
+    number: usize,
+    offset: usize,
 }
 
-const HTTP_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+impl SourceLine<'_> {
+    /// The span of `part`, which must be a slice of `self.raw`.
+    fn span(&self, part: &str) -> Span {
+        let start = part.as_ptr() as usize - self.raw.as_ptr() as usize;
+        Span {
+            start: self.offset + start,
+            end: self.offset + start + part.len(),
+            line: self.number,
+            column: self.raw[..start].chars().count() + 1,
+        }
+    }
+
+    fn error(&self, part: &str, message: String, help: &str) -> ReqxError {
+        ReqxError::Lex {
+            message,
+            help: Some(help.to_string()),
+            location: Location::new(self.span(part)),
+        }
+    }
+}
 
 /// Internal lexer state machine.
 struct Lexer {
@@ -46,9 +117,24 @@ impl Lexer {
         }
     }
 
-    /// Convenience: push a token with its line number.
-    fn push(&mut self, token: Token, line: usize) {
-        self.tokens.push(LocatedToken { token, line });
+    /// Convenience: push a token with its position.
+    fn push(&mut self, token: Token, span: Span) {
+        self.tokens.push(LocatedToken {
+            token,
+            line: span.line,
+            span,
+            value_span: None,
+        });
+    }
+
+    /// Push a token that has a value part, remembering where the value is.
+    fn push_with_value(&mut self, token: Token, span: Span, value_span: Span) {
+        self.tokens.push(LocatedToken {
+            token,
+            line: span.line,
+            span,
+            value_span: Some(value_span),
+        });
     }
 
     /// Returns the last non-blank token, if any.
@@ -63,18 +149,18 @@ impl Lexer {
     // ── Individual classifiers ───────────────────────────────────────
 
     /// `###` — request separator. Also resets body mode.
-    fn try_separator(&mut self, trimmed: &str, line: usize) -> bool {
+    fn try_separator(&mut self, trimmed: &str, line: &SourceLine) -> bool {
         if trimmed != "###" {
             return false;
         }
         self.in_body = false;
         self.has_request_line = false;
-        self.push(Token::Separator, line);
+        self.push(Token::Separator, line.span(trimmed));
         true
     }
 
     /// Empty / whitespace-only line. Detects transition into body mode.
-    fn try_blank(&mut self, trimmed: &str, line: usize) -> bool {
+    fn try_blank(&mut self, trimmed: &str, line: &SourceLine) -> bool {
         if !trimmed.is_empty() {
             return false;
         }
@@ -87,46 +173,149 @@ impl Lexer {
                 self.in_body = true;
             }
         }
-        self.push(Token::BlankLine, line);
+        self.push(Token::BlankLine, line.span(line.raw));
         true
     }
 
     /// `@name = value` — variable definition.
-    fn try_variable(&mut self, line_str: &str, line: usize) -> Result<bool, String> {
+    fn try_variable(&mut self, line_str: &str, line: &SourceLine) -> Result<bool, ReqxError> {
         if !line_str.starts_with('@') {
             return Ok(false);
         }
         let eq_pos = line_str.find('=').ok_or_else(|| {
-            format!(
-                "Line {}: invalid variable definition (missing '='): {}",
-                line, line_str
+            line.error(
+                line_str,
+                format!("invalid variable definition (missing '='): {}", line_str),
+                "variables are defined as `@name = value`",
             )
         })?;
         let name = line_str[1..eq_pos].trim().to_string();
         if name.is_empty() {
-            return Err(format!("Line {}: empty variable name", line));
+            return Err(line.error(
+                &line_str[..eq_pos + 1],
+                "empty variable name".to_string(),
+                "add a name between `@` and `=`",
+            ));
         }
-        let value = line_str[eq_pos + 1..].trim().to_string();
+        let value = line_str[eq_pos + 1..].trim();
+        let value_span = line.span(value);
 
         // If we find a variable, it marks the end of a body (extraction)
         self.in_body = false;
 
-        self.push(Token::Variable { name, value }, line);
+        self.push_with_value(
+            Token::Variable {
+                name,
+                value: value.to_string(),
+            },
+            line.span(line_str),
+            value_span,
+        );
         Ok(true)
     }
 
-    /// `# text` — comment (already guaranteed not to be `###`).
-    fn try_comment(&mut self, line_str: &str, line: usize) -> bool {
+    /// `? subject operator expected` — response assertion. Like variables,
+    /// assertions end the body.
+    fn try_assertion(&mut self, line_str: &str, line: &SourceLine) -> bool {
+        if !is_assertion(line_str) {
+            return false;
+        }
+        let text = line_str[1..].trim();
+        self.in_body = false;
+        self.push_with_value(
+            Token::Assertion(text.to_string()),
+            line.span(line_str),
+            line.span(text),
+        );
+        true
+    }
+
+    /// `> path` or `>> path` — output redirect. Like assertions, it ends the
+    /// body.
+    fn try_output(&mut self, line_str: &str, line: &SourceLine) -> Result<bool, ReqxError> {
+        let Some(rest) = line_str.strip_prefix('>') else {
+            return Ok(false);
+        };
+        let (append, rest) = match rest.strip_prefix('>') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return Ok(false);
+        }
+        let path = rest.trim();
+        if path.is_empty() {
+            return Err(line.error(
+                line_str,
+                "missing output path".to_string(),
+                "write `> ./response.json` to save the response body, or `>> ./log.json` to append it",
+            ));
+        }
+        self.in_body = false;
+        self.push_with_value(
+            Token::Output {
+                path: path.to_string(),
+                append,
+            },
+            line.span(line_str),
+            line.span(path),
+        );
+        Ok(true)
+    }
+
+    /// `  ?key=value` or `  &key=value` — an indented query parameter, right
+    /// below the URL or another query parameter.
+    fn try_query_param(&mut self, line_str: &str, line: &SourceLine) -> bool {
+        if !line.raw.starts_with(char::is_whitespace)
+            || !matches!(
+                self.last_meaningful_token(),
+                Some(Token::Url(_) | Token::QueryParam(_))
+            )
+        {
+            return false;
+        }
+        let Some(text) = line_str.strip_prefix(['?', '&']) else {
+            return false;
+        };
+        if text.is_empty() || text.starts_with(char::is_whitespace) {
+            return false;
+        }
+        self.push_with_value(
+            Token::QueryParam(text.to_string()),
+            line.span(line_str),
+            line.span(text),
+        );
+        true
+    }
+
+    /// `# text` — comment (already guaranteed not to be `###`), or
+    /// `# @name value` — directive.
+    fn try_comment(&mut self, line_str: &str, line: &SourceLine) -> bool {
         if !line_str.starts_with('#') {
             return false;
         }
-        let text = line_str[1..].trim().to_string();
-        self.push(Token::Comment(text), line);
+        let text = line_str[1..].trim();
+        if let Some(directive) = text.strip_prefix('@') {
+            let (name, value) = directive
+                .split_once(char::is_whitespace)
+                .unwrap_or((directive, &directive[directive.len()..]));
+            let value = value.trim();
+            self.push_with_value(
+                Token::Directive {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                },
+                line.span(line_str),
+                line.span(value),
+            );
+            return true;
+        }
+        self.push(Token::Comment(text.to_string()), line.span(line_str));
         true
     }
 
     /// `METHOD url` or just `url` — request line.
-    fn try_request_line(&mut self, trimmed: &str, line: usize) -> bool {
+    fn try_request_line(&mut self, trimmed: &str, line: &SourceLine) -> bool {
         if self.has_request_line {
             return false;
         }
@@ -135,10 +324,10 @@ impl Lexer {
         let upper = first_word.to_uppercase();
 
         if HTTP_METHODS.contains(&upper.as_str()) {
-            self.push(Token::Method(upper.clone()), line);
+            self.push(Token::Method(upper.clone()), line.span(first_word));
             let url = trimmed[upper.len()..].trim();
             if !url.is_empty() {
-                self.push(Token::Url(url.to_string()), line);
+                self.push(Token::Url(url.to_string()), line.span(url));
             }
             self.has_request_line = true;
             return true;
@@ -150,7 +339,7 @@ impl Lexer {
             || trimmed.starts_with("localhost")
             || trimmed.starts_with(':')
         {
-            self.push(Token::Url(trimmed.to_string()), line);
+            self.push(Token::Url(trimmed.to_string()), line.span(trimmed));
             self.has_request_line = true;
             return true;
         }
@@ -159,7 +348,7 @@ impl Lexer {
     }
 
     /// `Key: Value` — HTTP header (key must have no spaces).
-    fn try_header(&mut self, line_str: &str, line: usize) -> bool {
+    fn try_header(&mut self, line_str: &str, line: &SourceLine) -> bool {
         let Some(colon) = line_str.find(':') else {
             return false;
         };
@@ -167,13 +356,14 @@ impl Lexer {
         if key.is_empty() || key.contains(' ') {
             return false;
         }
-        let value = line_str[colon + 1..].trim().to_string();
-        self.push(
+        let value = line_str[colon + 1..].trim();
+        self.push_with_value(
             Token::Header {
                 key: key.to_string(),
-                value,
+                value: value.to_string(),
             },
-            line,
+            line.span(line_str),
+            line.span(value),
         );
         true
     }
@@ -181,7 +371,10 @@ impl Lexer {
     // ── Main entry point ─────────────────────────────────────────────
 
     /// Classify a single source line and append the resulting token(s).
-    fn classify_line(&mut self, raw_line: &str, line: usize) -> Result<(), String> {
+    /// `rest` holds the lines after it, which decide whether a `? ` or
+    /// `> path` line inside a body ends the body.
+    fn classify_line(&mut self, line: &SourceLine, rest: &[SourceLine]) -> Result<(), ReqxError> {
+        let raw_line = line.raw;
         let trimmed = raw_line.trim();
 
         // Order matters: separator must come before comment (both start with `#`).
@@ -194,11 +387,22 @@ impl Lexer {
         if self.try_variable(trimmed, line)? {
             return Ok(());
         }
+        // Inside a body, a `? ` or `> path` line is body text unless only
+        // assertions, extractions and outputs follow it: then the body ends
+        // there. Output paths ending a body cannot have spaces, so quoted text
+        // such as `> I agree` stays in the body.
+        let ends_body = !self.in_body || only_trailer(rest);
+        if ends_body && self.try_assertion(trimmed, line) {
+            return Ok(());
+        }
+        if ends_body && (!self.in_body || is_output(trimmed)) && self.try_output(trimmed, line)? {
+            return Ok(());
+        }
         if self.in_body {
-            self.push(Token::BodyLine(raw_line.to_string()), line);
+            self.push(Token::BodyLine(raw_line.to_string()), line.span(raw_line));
             return Ok(());
         }
-        if self.try_comment(trimmed, line) {
+        if self.try_query_param(trimmed, line) {
             return Ok(());
         }
         if self.try_comment(trimmed, line) {
@@ -212,23 +416,66 @@ impl Lexer {
         }
 
         if !self.has_request_line {
-            self.push(Token::Url(trimmed.to_string()), line);
+            self.push(Token::Url(trimmed.to_string()), line.span(trimmed));
             self.has_request_line = true;
             return Ok(());
         }
 
         // Fallback: treat unrecognised lines as body content.
-        self.push(Token::BodyLine(raw_line.to_string()), line);
+        self.push(Token::BodyLine(raw_line.to_string()), line.span(raw_line));
         Ok(())
     }
 }
 
+/// `? text` — an assertion line, once trimmed.
+fn is_assertion(trimmed: &str) -> bool {
+    trimmed
+        .strip_prefix('?')
+        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+}
+
+/// `> path` or `>> path` — an output line, once trimmed, whose path has no
+/// spaces.
+fn is_output(trimmed: &str) -> bool {
+    let rest = trimmed.trim_start_matches('>');
+    trimmed.len() - rest.len() <= 2
+        && rest.starts_with(char::is_whitespace)
+        && !rest.trim_start().contains(char::is_whitespace)
+}
+
+/// Whether `lines`, up to the next `###`, can all come after a body: blank
+/// lines, extractions, assertions and outputs.
+fn only_trailer(lines: &[SourceLine]) -> bool {
+    lines
+        .iter()
+        .map(|line| line.raw.trim())
+        .take_while(|trimmed| *trimmed != "###")
+        .all(|trimmed| {
+            trimmed.is_empty()
+                || trimmed.starts_with('@')
+                || is_assertion(trimmed)
+                || is_output(trimmed)
+        })
+}
+
 /// Tokenize the contents of a `.reqx` file.
-pub fn tokenize(input: &str) -> Result<Vec<LocatedToken>, String> {
+pub fn tokenize(input: &str) -> Result<Vec<LocatedToken>, ReqxError> {
     let mut lexer = Lexer::new();
 
-    for (idx, raw_line) in input.lines().enumerate() {
-        lexer.classify_line(raw_line, idx + 1)?;
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for (idx, chunk) in input.split_inclusive('\n').enumerate() {
+        let raw = chunk.strip_suffix('\n').unwrap_or(chunk);
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        lines.push(SourceLine {
+            raw,
+            number: idx + 1,
+            offset,
+        });
+        offset += chunk.len();
+    }
+    for (idx, line) in lines.iter().enumerate() {
+        lexer.classify_line(line, &lines[idx + 1..])?;
     }
 
     Ok(lexer.tokens)
@@ -271,6 +518,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_directive() {
+        let input = "# @name  login\n# @ping";
+        let tokens = tokenize(input).unwrap();
+        assert_eq!(
+            tokens[0].token,
+            Token::Directive {
+                name: "name".to_string(),
+                value: "login".to_string(),
+            }
+        );
+        let value_span = tokens[0].value_span.unwrap();
+        assert_eq!(&input[value_span.start..value_span.end], "login");
+        assert_eq!(
+            tokens[1].token,
+            Token::Directive {
+                name: "ping".to_string(),
+                value: String::new(),
+            }
+        );
+    }
+
     #[test]
     fn test_tokenize_method_and_url() {
         let input = "GET https://api.example.com/users";
@@ -305,6 +574,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_assertion_ends_body() {
+        let input = "POST :3000/users\n\n{\"a\": 1}\n? status == 201\n\n@id = id";
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        assert_eq!(kinds[3], &Token::BodyLine("{\"a\": 1}".to_string()));
+        assert_eq!(kinds[4], &Token::Assertion("status == 201".to_string()));
+        // Without a space after `?` the line is ordinary content.
+        let tokens = tokenize("POST :3000/users\n\n?not-an-assertion").unwrap();
+        assert!(matches!(tokens[3].token, Token::BodyLine(_)));
+    }
+
+    #[test]
+    fn test_tokenize_question_in_body() {
+        // Only the lines after the body are assertions.
+        let input = "POST :3000\n\nIs it?\n? yes it is\nthanks\n\n? status == 200";
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        assert_eq!(kinds[4], &Token::BodyLine("? yes it is".to_string()));
+        assert_eq!(kinds[5], &Token::BodyLine("thanks".to_string()));
+        assert_eq!(kinds[7], &Token::Assertion("status == 200".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_query_params() {
+        let input = "GET :3000/search\n  ?q={{query}}\n  &page=2\nAccept: */*\n  &late=1";
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        assert_eq!(kinds[2], &Token::QueryParam("q={{query}}".to_string()));
+        assert_eq!(kinds[3], &Token::QueryParam("page=2".to_string()));
+        assert!(matches!(kinds[4], Token::Header { .. }));
+        // Only right below the URL.
+        assert!(!matches!(kinds[5], Token::QueryParam(_)));
+        let span = tokens[2].value_span.unwrap();
+        assert_eq!((span.line, span.column), (2, 4));
+    }
+
+    #[test]
+    fn test_tokenize_output() {
+        let input = "POST :3000\n\n<a\n>\n</a>\n>> ./out/{{id}}.xml\n? status == 200";
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        // A lone `>` inside the body is body text.
+        assert_eq!(kinds[4], &Token::BodyLine(">".to_string()));
+        assert_eq!(
+            kinds[6],
+            &Token::Output {
+                path: "./out/{{id}}.xml".to_string(),
+                append: true,
+            }
+        );
+        assert!(matches!(kinds[7], Token::Assertion(_)));
+        let span = tokens[6].value_span.unwrap();
+        assert_eq!((span.line, span.column), (6, 4));
+
+        let err = tokenize("GET :3000\n> ").unwrap_err();
+        assert_eq!(err.message(), "missing output path");
+    }
+
+    #[test]
+    fn test_tokenize_quote_in_body() {
+        let input = "POST :3000\n\n> Quoted reply from the user\n> ./not-a-path\nThanks!";
+        let tokens = tokenize(input).unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        assert_eq!(
+            kinds[3],
+            &Token::BodyLine("> Quoted reply from the user".to_string())
+        );
+        // Body text follows, so this is not the end of the body either.
+        assert_eq!(kinds[4], &Token::BodyLine("> ./not-a-path".to_string()));
+
+        // As the last line of a body too.
+        let tokens = tokenize("POST :3000\n\nHi,\n> Quoted reply from the user").unwrap();
+        assert!(tokens
+            .iter()
+            .all(|t| !matches!(t.token, Token::Output { .. })));
+
+        // Without a body, the path may have spaces.
+        let tokens = tokenize("GET :3000\n> ./my report.json").unwrap();
+        assert!(matches!(tokens[2].token, Token::Output { .. }));
+    }
+
     #[test]
     fn test_tokenize_full_request() {
         let input = r#"@token = abc123
@@ -343,4 +694,38 @@ Accept: application/json
         assert!(found_method, "should find method");
         assert!(found_body, "should find body");
     }
+
+    #[test]
+    fn test_token_spans() {
+        let input = "@host = example.com\r\n\r\n  GET https://{{host}}/users\nAccept: */*";
+        let tokens = tokenize(input).unwrap();
+
+        let url = tokens
+            .iter()
+            .find(|t| matches!(t.token, Token::Url(_)))
+            .unwrap();
+        assert_eq!(url.span.line, 3);
+        assert_eq!(url.span.column, 7);
+        assert_eq!(
+            &input[url.span.start..url.span.end],
+            "https://{{host}}/users"
+        );
+
+        let header = tokens.last().unwrap();
+        assert_eq!(header.span.line, 4);
+        assert_eq!(header.span.column, 1);
+        assert_eq!(&input[header.span.start..header.span.end], "Accept: */*");
+    }
+
+    #[test]
+    fn test_tokenize_error_location() {
+        let err = tokenize("GET https://example.com\n  @broken").unwrap_err();
+        match err {
+            ReqxError::Lex { location, .. } => {
+                assert_eq!(location.span.line, 2);
+                assert_eq!(location.span.column, 3);
+            }
+            other => panic!("expected a lex error, got {:?}", other),
+        }
+    }
 }
@@ -1,5 +1,19 @@
+mod assertion;
 pub mod ast;
 pub mod client;
+mod dynamic;
+pub mod environment;
+pub mod error;
+mod extraction;
+pub mod filter;
 pub mod interpreter;
+pub mod jsonpath;
 pub mod lexer;
+mod markup;
+mod multipart;
+mod output;
 pub mod parser;
+mod reference;
+pub mod report;
+pub mod reporter;
+pub mod span;
@@ -0,0 +1,265 @@
+//! XPath and CSS selector queries against XML and HTML response bodies, used
+//! by `@id = xpath //user/@id` and `@title = css h1.title` extractions.
+//!
+//! Namespaces are ignored: `//user` matches `<m:user xmlns:m="...">`.
+
+use crate::client::HttpResponse;
+use scraper::{ElementRef, Html, Node, Selector};
+use sxd_document::dom::{Document, Element};
+use sxd_document::Package;
+use sxd_xpath::{Context, Factory, Value, XPath};
+
+/// How a response body is parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Markup {
+    Xml,
+    Html,
+}
+
+impl Markup {
+    /// The markup `response` is written in, from its `Content-Type`, or `None`
+    /// for anything that is neither XML nor HTML.
+    pub fn of(response: &HttpResponse) -> Option<Markup> {
+        let content_type = response.header("content-type")?.to_ascii_lowercase();
+        if content_type.contains("html") {
+            Some(Markup::Html)
+        } else if content_type.contains("xml") {
+            Some(Markup::Xml)
+        } else {
+            None
+        }
+    }
+
+    /// Like `of`, but guess from the body when the `Content-Type` does not say.
+    fn detect(response: &HttpResponse) -> Markup {
+        Markup::of(response).unwrap_or_else(|| {
+            let body = response.text();
+            let start = body.trim_start();
+            let start = start.get(..15).unwrap_or(start).to_ascii_lowercase();
+            if start.starts_with("<!doctype html") || start.starts_with("<html") {
+                Markup::Html
+            } else {
+                Markup::Xml
+            }
+        })
+    }
+}
+
+/// Compile an XPath expression, or say why it is invalid.
+pub(crate) fn compile_xpath(expr: &str) -> Result<XPath, String> {
+    match Factory::new().build(expr) {
+        Ok(Some(xpath)) => Ok(xpath),
+        Ok(None) => Err("the XPath expression is empty".to_string()),
+        Err(e) => Err(format!("invalid XPath `{}`: {}", expr, e)),
+    }
+}
+
+/// Parse a CSS selector, or say why it is invalid.
+pub(crate) fn parse_selector(selector: &str) -> Result<Selector, String> {
+    Selector::parse(selector).map_err(|e| format!("invalid CSS selector `{}`: {}", selector, e))
+}
+
+/// Evaluate `expr` against the response body, parsed as XML or HTML depending
+/// on the response. A node set gives the text of its first node.
+pub(crate) fn xpath(expr: &str, response: &HttpResponse) -> Result<Option<String>, String> {
+    let xpath = compile_xpath(expr)?;
+    let package = match Markup::detect(response) {
+        Markup::Xml => parse_xml(&response.text())?,
+        Markup::Html => html_to_xml(&Html::parse_document(&response.text())),
+    };
+    let document = package.as_document();
+    let value = xpath
+        .evaluate(&Context::new(), document.root())
+        .map_err(|e| format!("cannot evaluate XPath `{}`: {}", expr, e))?;
+    Ok(match value {
+        Value::Nodeset(ref nodes) if nodes.size() == 0 => None,
+        value => Some(value.into_string()),
+    })
+}
+
+/// The text of the first element matching `selector` in the response body,
+/// or the value of its `attribute` when one is given. `None` if no element
+/// matches.
+pub(crate) fn css(
+    selector: &str,
+    attribute: Option<&str>,
+    response: &HttpResponse,
+) -> Result<Option<String>, String> {
+    let parsed = parse_selector(selector)?;
+    let html = Html::parse_document(&response.text());
+    let Some(element) = html.select(&parsed).next() else {
+        return Ok(None);
+    };
+    Ok(match attribute {
+        Some(name) => Some(
+            element
+                .value()
+                .attr(name)
+                .ok_or_else(|| {
+                    format!(
+                        "the first `{}` element has no `{}` attribute",
+                        selector, name
+                    )
+                })?
+                .to_string(),
+        ),
+        None => Some(element.text().collect::<String>().trim().to_string()),
+    })
+}
+
+fn parse_xml(body: &str) -> Result<Package, String> {
+    let package = sxd_document::parser::parse(body)
+        .map_err(|_| "the response body is not valid XML".to_string())?;
+    for child in package.as_document().root().children() {
+        if let Some(element) = child.element() {
+            strip_namespaces(element);
+        }
+    }
+    Ok(package)
+}
+
+/// Drop the namespace of `element`, its attributes and its descendants, so
+/// that XPath name tests need no prefixes.
+fn strip_namespaces(element: Element) {
+    element.set_name(element.name().local_part());
+    for attribute in element.attributes() {
+        let name = attribute.name();
+        if name.namespace_uri().is_some() {
+            let value = attribute.value();
+            attribute.remove_from_parent();
+            element.set_attribute_value(name.local_part(), value);
+        }
+    }
+    for child in element.children() {
+        if let Some(child) = child.element() {
+            strip_namespaces(child);
+        }
+    }
+}
+
+/// Copy the elements and text of an HTML document into an XML document that
+/// XPath can query.
+fn html_to_xml(html: &Html) -> Package {
+    let package = Package::new();
+    {
+        let document = package.as_document();
+        document
+            .root()
+            .append_child(copy_element(document, html.root_element()));
+    }
+    package
+}
+
+fn copy_element<'d>(document: Document<'d>, source: ElementRef) -> Element<'d> {
+    let element = document.create_element(source.value().name());
+    for (name, value) in source.value().attrs() {
+        element.set_attribute_value(name, value);
+    }
+    for child in source.children() {
+        if let Some(child) = ElementRef::wrap(child) {
+            element.append_child(copy_element(document, child));
+        } else if let Node::Text(text) = child.value() {
+            element.append_child(document.create_text(text));
+        }
+    }
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content_type: &str, body: &str) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            status_is_success: true,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: vec![("content-type".to_string(), content_type.to_string())],
+            body: body.into(),
+        }
+    }
+
+    const SOAP: &str = r#"<?xml version="1.0"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetUserResponse xmlns="urn:users">
+      <user id="7"><name>Ada</name></user>
+      <user id="8"><name>Bob</name></user>
+    </GetUserResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Users</title></head>
+<body>
+  <h1 class="title"> All users </h1>
+  <a class="next" href="/users?page=2">Next</a>
+  <br>
+  <p>unclosed
+</body></html>"#;
+
+    #[test]
+    fn test_xpath_on_xml() {
+        let r = response("text/xml; charset=utf-8", SOAP);
+        let x = |expr| xpath(expr, &r).unwrap();
+        assert_eq!(x("//user/@id").as_deref(), Some("7"));
+        assert_eq!(x("//user[2]/name").as_deref(), Some("Bob"));
+        assert_eq!(x("/Envelope/Body//name").as_deref(), Some("Ada"));
+        assert_eq!(x("count(//user)").as_deref(), Some("2"));
+        assert_eq!(x("//missing"), None);
+    }
+
+    #[test]
+    fn test_xpath_on_html() {
+        let r = response("text/html", PAGE);
+        let x = |expr| xpath(expr, &r).unwrap();
+        assert_eq!(x("//title").as_deref(), Some("Users"));
+        assert_eq!(
+            x("//a[@class='next']/@href").as_deref(),
+            Some("/users?page=2")
+        );
+    }
+
+    #[test]
+    fn test_css() {
+        let r = response("text/html", PAGE);
+        assert_eq!(
+            css("h1.title", None, &r).unwrap().as_deref(),
+            Some("All users")
+        );
+        assert_eq!(
+            css("a.next", Some("href"), &r).unwrap().as_deref(),
+            Some("/users?page=2")
+        );
+        assert_eq!(
+            css("a.next", Some("rel"), &r).unwrap_err(),
+            "the first `a.next` element has no `rel` attribute"
+        );
+        assert_eq!(css("table", None, &r).unwrap(), None);
+    }
+
+    #[test]
+    fn test_markup_detection() {
+        assert_eq!(
+            Markup::of(&response("application/soap+xml", "")),
+            Some(Markup::Xml)
+        );
+        assert_eq!(Markup::of(&response("text/html", "")), Some(Markup::Html));
+        assert_eq!(Markup::of(&response("application/json", "")), None);
+        let untyped = |body| Markup::detect(&response("", body));
+        assert_eq!(untyped("  <!DOCTYPE html><html></html>"), Markup::Html);
+        assert_eq!(untyped("<users/>"), Markup::Xml);
+        // Byte 15 falls inside a character.
+        assert_eq!(untyped(&"é".repeat(10)), Markup::Xml);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(compile_xpath("//user[").is_err());
+        assert!(compile_xpath("").is_err());
+        assert!(parse_selector("h1..title").is_err());
+        let err = xpath("//user", &response("text/xml", "<users>")).unwrap_err();
+        assert_eq!(err, "the response body is not valid XML");
+    }
+}
@@ -0,0 +1,139 @@
+//! Encoding `multipart/form-data` bodies (RFC 7578) from their resolved parts.
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+/// A part whose headers and contents have been interpolated or read.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ResolvedPart {
+    pub name: String,
+    /// The file name sent for `< file` parts.
+    pub filename: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub content: Vec<u8>,
+}
+
+/// A new random boundary. It does not come from the seeded generator of
+/// built-in variables, so multipart requests do not change their values.
+pub(crate) fn boundary() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect();
+    format!("reqx-{}", random)
+}
+
+/// The `Content-Type` of a multipart body delimited by `boundary`.
+pub(crate) fn content_type(boundary: &str) -> String {
+    format!("multipart/form-data; boundary={}", boundary)
+}
+
+/// The body of `parts`, delimited by `boundary`. Each part gets a
+/// `Content-Disposition` header unless it has one, and file parts default to
+/// `application/octet-stream`.
+pub(crate) fn encode(parts: &[ResolvedPart], boundary: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        let has = |name: &str| {
+            part.headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case(name))
+        };
+        if !has("content-disposition") {
+            let mut disposition = format!("form-data; name=\"{}\"", escape(&part.name));
+            if let Some(filename) = &part.filename {
+                disposition.push_str(&format!("; filename=\"{}\"", escape(filename)));
+            }
+            push_header(&mut body, "Content-Disposition", &disposition);
+        }
+        if part.filename.is_some() && !has("content-type") {
+            push_header(&mut body, "Content-Type", "application/octet-stream");
+        }
+        for (key, value) in &part.headers {
+            push_header(&mut body, key, value);
+        }
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(&part.content);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// `value` percent-encoded for a quoted `Content-Disposition` parameter: `"`
+/// and line breaks would end the parameter or the header.
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn push_header(body: &mut Vec<u8>, key: &str, value: &str) {
+    body.extend_from_slice(format!("{}: {}\r\n", key, value).as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let parts = [
+            ResolvedPart {
+                name: "title".to_string(),
+                filename: None,
+                headers: Vec::new(),
+                content: b"Holiday".to_vec(),
+            },
+            ResolvedPart {
+                name: "photo".to_string(),
+                filename: Some("beach.png".to_string()),
+                headers: vec![("Content-Type".to_string(), "image/png".to_string())],
+                content: vec![0x89, b'P', b'N', b'G'],
+            },
+        ];
+        let mut expected = b"--b1\r\n\
+            Content-Disposition: form-data; name=\"title\"\r\n\
+            \r\n\
+            Holiday\r\n\
+            --b1\r\n\
+            Content-Disposition: form-data; name=\"photo\"; filename=\"beach.png\"\r\n\
+            Content-Type: image/png\r\n\
+            \r\n"
+            .to_vec();
+        expected.extend_from_slice(&[0x89, b'P', b'N', b'G']);
+        expected.extend_from_slice(b"\r\n--b1--\r\n");
+        assert_eq!(encode(&parts, "b1"), expected);
+        assert_eq!(content_type("b1"), "multipart/form-data; boundary=b1");
+    }
+
+    #[test]
+    fn test_encode_file_defaults() {
+        let parts = [ResolvedPart {
+            name: "data".to_string(),
+            filename: Some("dump.bin".to_string()),
+            headers: Vec::new(),
+            content: Vec::new(),
+        }];
+        let body = String::from_utf8(encode(&parts, "x")).unwrap();
+        assert!(body.contains("Content-Type: application/octet-stream\r\n"));
+    }
+
+    #[test]
+    fn test_encode_escapes_names() {
+        let parts = [ResolvedPart {
+            name: "a\"b".to_string(),
+            filename: Some("evil\".pdf\r\nX-Injected: 1".to_string()),
+            headers: Vec::new(),
+            content: Vec::new(),
+        }];
+        let body = String::from_utf8(encode(&parts, "x")).unwrap();
+        assert!(body.contains(
+            "Content-Disposition: form-data; name=\"a%22b\"; filename=\"evil%22.pdf%0D%0AX-Injected: 1\"\r\n"
+        ));
+        assert_ne!(boundary(), boundary());
+    }
+}
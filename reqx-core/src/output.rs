@@ -0,0 +1,160 @@
+//! Saving responses to files, for `> path` and `>> path` redirects and
+//! `ExecuteOptions::output_dir`.
+
+use crate::ast::Request;
+use crate::client::HttpResponse;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Write `contents` to `path`, creating its parent directories. `append` adds
+/// to the end of an existing file instead of replacing it.
+pub(crate) fn write(path: &Path, contents: &[u8], append: bool) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?
+        .write_all(contents)
+}
+
+/// The file name, without extension, of request `index` (zero-based) in an
+/// output directory: its `@name`, or `request-<n>` for unnamed requests.
+/// Characters other than letters, digits, `-` and `_` become `_`, so the name
+/// cannot point outside the directory. When a request in `named` is itself
+/// called `request-<n>`, the unnamed one becomes `request-<n>-2` (or `-3`...).
+pub(crate) fn file_stem(req: &Request, index: usize, named: &HashMap<&str, usize>) -> String {
+    match &req.name {
+        Some(name) => name
+            .chars()
+            .map(|c| match c.is_alphanumeric() || matches!(c, '-' | '_') {
+                true => c,
+                false => '_',
+            })
+            .collect(),
+        None => {
+            let stem = format!("request-{}", index + 1);
+            (1..)
+                .map(|k| match k {
+                    1 => stem.clone(),
+                    k => format!("{}-{}", stem, k),
+                })
+                .find(|candidate| !named.contains_key(candidate.as_str()))
+                .unwrap()
+        }
+    }
+}
+
+/// The file extension matching the response's `Content-Type`.
+pub(crate) fn extension(response: &HttpResponse) -> &'static str {
+    let Some(content_type) = response.content_type() else {
+        return match response.is_binary() {
+            true => "bin",
+            false => "txt",
+        };
+    };
+    let content_type = content_type.to_ascii_lowercase();
+    let (kind, subtype) = content_type.split_once('/').unwrap_or((&content_type, ""));
+    // `application/problem+json` is JSON, `image/svg+xml` is XML.
+    let subtype = subtype.rsplit('+').next().unwrap_or(subtype);
+    match subtype {
+        "json" => "json",
+        "xml" => "xml",
+        "html" => "html",
+        "csv" => "csv",
+        "pdf" => "pdf",
+        "zip" => "zip",
+        "gzip" | "x-gzip" => "gz",
+        "png" => "png",
+        "jpeg" => "jpg",
+        "gif" => "gif",
+        "webp" => "webp",
+        "javascript" => "js",
+        "css" => "css",
+        _ if kind == "text" => "txt",
+        _ => "bin",
+    }
+}
+
+/// The status line and headers of `response`, one per line.
+pub(crate) fn headers_text(response: &HttpResponse) -> String {
+    let mut text = format!("HTTP {}\n", response.status);
+    for (key, value) in &response.headers {
+        text.push_str(&format!("{}: {}\n", key, value));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content_type: Option<&str>, body: &[u8]) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            status_is_success: true,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: content_type
+                .map(|ct| vec![("Content-Type".to_string(), ct.to_string())])
+                .unwrap_or_default(),
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_extension() {
+        let cases = [
+            (Some("application/json; charset=utf-8"), "json"),
+            (Some("application/problem+json"), "json"),
+            (Some("image/svg+xml"), "xml"),
+            (Some("text/html"), "html"),
+            (Some("text/plain"), "txt"),
+            (Some("image/jpeg"), "jpg"),
+            (Some("application/octet-stream"), "bin"),
+            (None, "txt"),
+        ];
+        for (content_type, expected) in cases {
+            assert_eq!(extension(&response(content_type, b"ok")), expected);
+        }
+        assert_eq!(extension(&response(None, &[0xff, 0x00])), "bin");
+    }
+
+    #[test]
+    fn test_file_stem() {
+        let request = |name: Option<&str>| {
+            let mut file =
+                crate::parser::parse(crate::lexer::tokenize("GET :3000").unwrap()).unwrap();
+            let mut req = file.requests.remove(0);
+            req.name = name.map(str::to_string);
+            req
+        };
+        let none = HashMap::new();
+        assert_eq!(
+            file_stem(&request(Some("get-user_2")), 0, &none),
+            "get-user_2"
+        );
+        assert_eq!(
+            file_stem(&request(Some("../etc/passwd")), 0, &none),
+            "___etc_passwd"
+        );
+        assert_eq!(file_stem(&request(None), 2, &none), "request-3");
+
+        let named = HashMap::from([("request-3", 0), ("request-3-2", 1)]);
+        assert_eq!(file_stem(&request(None), 2, &named), "request-3-3");
+        assert_eq!(file_stem(&request(None), 3, &named), "request-4");
+    }
+
+    #[test]
+    fn test_headers_text() {
+        assert_eq!(
+            headers_text(&response(Some("text/plain"), b"")),
+            "HTTP 200\nContent-Type: text/plain\n"
+        );
+    }
+}
@@ -1,11 +1,45 @@
 /// Parser: converts a token stream into the AST.
-use crate::ast::{Header, HttpMethod, Request, ReqxFile, Variable};
+use crate::ast::{
+    Assertion, AssertionOperator, AssertionSubject, Body, Extract, ExtractSource, Header,
+    HttpMethod, Output, Param, Part, Request, ReqxFile, Variable,
+};
+use crate::error::{Location, ReqxError};
+use crate::jsonpath;
 use crate::lexer::{LocatedToken, Token};
+use crate::markup;
+use crate::span::Span;
+use std::collections::HashMap;
 
-/// Parse a list of tokens into a `ReqxFile` AST.
-pub fn parse(tokens: Vec<LocatedToken>) -> Result<ReqxFile, String> {
+type TokenIter = std::iter::Peekable<std::vec::IntoIter<LocatedToken>>;
+
+/// A parsed file together with every error found while parsing it.
+#[derive(Debug, Clone)]
+pub struct ParseOutput {
+    /// Every request that parsed successfully. Broken requests are left out.
+    pub file: ReqxFile,
+    pub errors: Vec<ReqxError>,
+}
+
+/// Parse a list of tokens into a `ReqxFile` AST, stopping at the first error.
+pub fn parse(tokens: Vec<LocatedToken>) -> Result<ReqxFile, ReqxError> {
+    let output = parse_recovering(tokens);
+    match output.errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(output.file),
+    }
+}
+
+/// Parse a list of tokens into a `ReqxFile` AST, collecting every error.
+///
+/// When a request fails to parse, the parser skips ahead to the next `###`
+/// separator and carries on, so a single pass reports all broken requests
+/// and the returned file still holds the valid ones.
+pub fn parse_recovering(tokens: Vec<LocatedToken>) -> ParseOutput {
     let mut variables = Vec::new();
     let mut requests = Vec::new();
+    let mut errors = Vec::new();
+    // Request names seen so far, with the line of their request.
+    let mut names: HashMap<String, usize> = HashMap::new();
 
     let mut iter = tokens.into_iter().peekable();
 
@@ -14,32 +48,30 @@ pub fn parse(tokens: Vec<LocatedToken>) -> Result<ReqxFile, String> {
         match &lt.token {
             Token::Variable { .. } => {
                 let lt = iter.next().unwrap();
-                if let Token::Variable { name, value } = lt.token {
-                    variables.push(Variable {
-                        name,
-                        value,
-                        line: lt.line,
-                    });
-                }
+                variables.push(to_variable(lt));
             }
             Token::BlankLine => {
                 iter.next();
             }
             Token::Comment(_) => {
-                // Check if this comment is followed (possibly after blanks) by a Method token.
-                // If so, it belongs to a request — don't consume it here.
-                let remaining: Vec<LocatedToken> = iter.clone().collect();
-                let next_meaningful = remaining
-                    .iter()
-                    .skip(1)
-                    .find(|t| t.token != Token::BlankLine);
+                // Check if this comment is followed (possibly after blanks, other
+                // comments and directives) by a request line. If so, it belongs
+                // to a request — don't consume it here.
+                let next_meaningful = iter.clone().skip(1).find(|t| {
+                    !matches!(
+                        t.token,
+                        Token::BlankLine | Token::Comment(_) | Token::Directive { .. }
+                    )
+                });
                 if let Some(nlt) = next_meaningful {
-                    if matches!(&nlt.token, Token::Method(_)) {
+                    if matches!(&nlt.token, Token::Method(_) | Token::Url(_)) {
                         break; // let the request parser handle this comment
                     }
                 }
                 iter.next();
             }
+            // Directives always belong to the request that follows them.
+            Token::Directive { .. } => break,
             Token::Separator => {
                 iter.next();
                 break;
@@ -65,13 +97,7 @@ pub fn parse(tokens: Vec<LocatedToken>) -> Result<ReqxFile, String> {
                     // after a separator.
 
                     let lt = iter.next().unwrap();
-                    if let Token::Variable { name, value } = lt.token {
-                        variables.push(Variable {
-                            name,
-                            value,
-                            line: lt.line,
-                        });
-                    }
+                    variables.push(to_variable(lt));
                 }
                 _ => break,
             }
@@ -81,93 +107,316 @@ pub fn parse(tokens: Vec<LocatedToken>) -> Result<ReqxFile, String> {
             break;
         }
 
-        match parse_request(&mut iter) {
-            Ok(req) => requests.push(req),
-            Err(e) => return Err(e),
+        match parse_request(&mut iter, &names) {
+            Ok(req) => {
+                if let Some(ref name) = req.name {
+                    names.insert(name.clone(), req.line);
+                }
+                requests.push(req);
+            }
+            Err(e) => {
+                errors.push(e);
+                // Resynchronise on the next request separator.
+                while iter.next_if(|lt| lt.token != Token::Separator).is_some() {}
+            }
         }
     }
 
-    Ok(ReqxFile {
-        variables,
-        requests,
-    })
+    ParseOutput {
+        file: ReqxFile {
+            variables,
+            requests,
+        },
+        errors,
+    }
 }
 
-fn parse_comment(
-    iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>,
-) -> Option<String> {
-    let mut comment = None;
+const URL_HELP: &str = "put the URL on the same line as the method, e.g. `GET https://example.com`";
+const REQUEST_LINE_HELP: &str =
+    "a request starts with `METHOD url` or just a URL, e.g. `GET https://example.com`";
+
+fn parse_error(message: String, help: Option<&str>, span: Span) -> ReqxError {
+    ReqxError::Parse {
+        message,
+        help: help.map(str::to_string),
+        location: Location::new(span),
+    }
+}
+
+/// Convert a `Token::Variable` into its AST node.
+fn to_variable(lt: LocatedToken) -> Variable {
+    let value_span = lt.value_span.unwrap_or(lt.span);
+    match lt.token {
+        Token::Variable { name, value } => Variable {
+            name,
+            value,
+            line: lt.line,
+            span: lt.span,
+            value_span,
+        },
+        other => unreachable!("expected a variable token, found {:?}", other),
+    }
+}
+
+/// The comment and directives above a request line.
+#[derive(Default)]
+struct Preamble {
+    comment: Option<String>,
+    name: Option<String>,
+    no_interpolate: bool,
+}
+
+const DIRECTIVE_HELP: &str =
+    "the supported directives are `# @name <request-name>` and `# @no-interpolate`";
+
+fn parse_preamble(
+    iter: &mut TokenIter,
+    names: &HashMap<String, usize>,
+) -> Result<Preamble, ReqxError> {
+    let mut preamble = Preamble::default();
     while let Some(lt) = iter.peek() {
         match &lt.token {
             Token::Comment(_) => {
                 let lt = iter.next().unwrap();
                 if let Token::Comment(text) = lt.token {
-                    comment = Some(text);
+                    preamble.comment = Some(text);
                 }
             }
+            Token::Directive { .. } => {
+                let lt = iter.next().unwrap();
+                parse_directive(lt, &mut preamble, names)?;
+            }
             Token::BlankLine => {
                 iter.next();
             }
             _ => break,
         }
     }
-    comment
+    Ok(preamble)
 }
 
-fn parse_method_and_url(
-    iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>,
-) -> Result<(Option<HttpMethod>, String, usize), String> {
-    let first_token = iter
-        .next()
-        .ok_or_else(|| "Unexpected end of input: expected HTTP method or URL".to_string())?;
+fn parse_directive(
+    lt: LocatedToken,
+    preamble: &mut Preamble,
+    names: &HashMap<String, usize>,
+) -> Result<(), ReqxError> {
+    let value_span = lt.value_span.unwrap_or(lt.span);
+    let Token::Directive { name, value } = lt.token else {
+        unreachable!("expected a directive token");
+    };
+    match name.as_str() {
+        "name" => {
+            if value.is_empty() {
+                return Err(parse_error(
+                    "`@name` needs a request name".to_string(),
+                    Some(DIRECTIVE_HELP),
+                    lt.span,
+                ));
+            }
+            if !value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(parse_error(
+                    format!("invalid request name `{}`", value),
+                    Some("request names may only contain letters, digits, `_` and `-`"),
+                    value_span,
+                ));
+            }
+            if preamble.name.is_some() {
+                return Err(parse_error(
+                    "a request can only have one `@name`".to_string(),
+                    None,
+                    lt.span,
+                ));
+            }
+            if let Some(line) = names.get(&value) {
+                return Err(parse_error(
+                    format!("duplicate request name `{}`", value),
+                    Some(&format!(
+                        "the request on line {} already has this name; pick another one",
+                        line
+                    )),
+                    value_span,
+                ));
+            }
+            preamble.name = Some(value);
+            Ok(())
+        }
+        "no-interpolate" => {
+            if !value.is_empty() {
+                return Err(parse_error(
+                    "`@no-interpolate` takes no value".to_string(),
+                    Some(DIRECTIVE_HELP),
+                    value_span,
+                ));
+            }
+            preamble.no_interpolate = true;
+            Ok(())
+        }
+        _ => Err(parse_error(
+            format!("unknown directive `@{}`", name),
+            Some(DIRECTIVE_HELP),
+            lt.span,
+        )),
+    }
+}
+
+/// The method (if explicit), URL and spans of a request line.
+struct RequestLine {
+    method: Option<HttpMethod>,
+    /// Whether the method is `GRAPHQL`.
+    graphql: bool,
+    url: String,
+    line: usize,
+    span: Span,
+    url_span: Span,
+}
+
+/// Parse the request line. Tokens that cannot belong to it (such as a `###`)
+/// are left in the iterator so error recovery can resynchronise on them.
+fn parse_method_and_url(iter: &mut TokenIter, last_span: Span) -> Result<RequestLine, ReqxError> {
+    let Some(first_token) = iter.next_if(|lt| lt.token != Token::Separator) else {
+        let (message, span) = match iter.peek() {
+            Some(lt) => (
+                format!("expected HTTP method or URL, found {}", lt.token.describe()),
+                lt.span,
+            ),
+            None => (
+                "Unexpected end of input: expected HTTP method or URL".to_string(),
+                last_span,
+            ),
+        };
+        return Err(parse_error(message, Some(REQUEST_LINE_HELP), span));
+    };
 
     let req_line = first_token.line;
+    let method_span = first_token.span;
 
     match first_token.token {
         Token::Method(m_str) => {
-            let method = m_str
-                .parse::<HttpMethod>()
-                .ok()
-                .ok_or_else(|| format!("Line {}: unsupported HTTP method: {}", req_line, m_str))?;
-
-            let url_token = iter
-                .next()
-                .ok_or_else(|| format!("Line {}: expected URL after method", req_line))?;
-
-            let url = match url_token.token {
-                Token::Url(u) => u,
-                other => {
-                    return Err(format!(
-                        "Line {}: expected URL, found {:?}",
-                        url_token.line, other
-                    ));
-                }
+            let graphql = m_str == GRAPHQL;
+            let method = match graphql {
+                true => HttpMethod::Post,
+                false => m_str.parse::<HttpMethod>().ok().ok_or_else(|| {
+                    parse_error(
+                        format!("unsupported HTTP method: {}", m_str),
+                        None,
+                        method_span,
+                    )
+                })?,
+            };
+
+            let Some(url_token) = iter.next_if(|lt| matches!(lt.token, Token::Url(_))) else {
+                let message = match iter.peek() {
+                    Some(lt) => format!("expected URL, found {}", lt.token.describe()),
+                    None => "expected URL after method".to_string(),
+                };
+                // A URL always shares the method's line, so a missing one
+                // is reported right after the method.
+                let after_method = Span {
+                    start: method_span.end,
+                    end: method_span.end,
+                    line: method_span.line,
+                    column: method_span.column + (method_span.end - method_span.start),
+                };
+                return Err(parse_error(message, Some(URL_HELP), after_method));
             };
-            Ok((Some(method), url, req_line))
+
+            let url_span = url_token.span;
+            let Token::Url(url) = url_token.token else {
+                unreachable!("next_if only yields URL tokens");
+            };
+            Ok(RequestLine {
+                method: Some(method),
+                graphql,
+                url,
+                line: req_line,
+                span: method_span.to(url_span),
+                url_span,
+            })
         }
-        Token::Url(u) => Ok((None, u, req_line)),
-        other => Err(format!(
-            "Line {}: expected HTTP method or URL, found {:?}",
-            req_line, other
+        Token::Url(u) => Ok(RequestLine {
+            method: None,
+            graphql: false,
+            url: u,
+            line: req_line,
+            span: method_span,
+            url_span: method_span,
+        }),
+        other => Err(parse_error(
+            format!("expected HTTP method or URL, found {}", other.describe()),
+            Some(REQUEST_LINE_HELP),
+            method_span,
         )),
     }
 }
 
-fn parse_headers(iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>) -> Vec<Header> {
+/// The indented `?key=value` / `&key=value` lines below the URL.
+fn parse_query(iter: &mut TokenIter) -> Result<Vec<Param>, ReqxError> {
+    let mut query = Vec::new();
+    while let Some(lt) = iter.next_if(|lt| matches!(lt.token, Token::QueryParam(_))) {
+        if let Token::QueryParam(text) = lt.token {
+            query.push(to_param(&text, lt.value_span.unwrap_or(lt.span))?);
+        }
+    }
+    Ok(query)
+}
+
+const PARAM_HELP: &str = "write one `key=value` per line, such as `?page=2` below the URL or `&name=Jane` in a form body";
+
+/// A `key=value` query or form parameter; `key` alone has an empty value.
+fn to_param(text: &str, span: Span) -> Result<Param, ReqxError> {
+    let (key, value) = text.split_once('=').unwrap_or((text, ""));
+    let key = key.trim_end();
+    if key.is_empty() {
+        return Err(parse_error(
+            format!("missing parameter name in `{}`", text),
+            Some(PARAM_HELP),
+            span,
+        ));
+    }
+    let value_start = text.len() - value.trim_start().len();
+    let value = value.trim();
+    Ok(Param {
+        key: key.to_string(),
+        value: value.to_string(),
+        key_span: span.subspan(text, 0..key.len()),
+        value_span: span.subspan(text, value_start..value_start + value.len()),
+    })
+}
+
+fn parse_headers(iter: &mut TokenIter) -> Vec<Header> {
     let mut headers = Vec::new();
     while let Some(lt) = iter.peek() {
         match &lt.token {
             Token::Header { .. } => {
                 let lt = iter.next().unwrap();
+                let value_span = lt.value_span.unwrap_or(lt.span);
                 if let Token::Header { key, value } = lt.token {
-                    headers.push(Header { key, value });
+                    let key_span = Span {
+                        end: lt.span.start + key.len(),
+                        ..lt.span
+                    };
+                    headers.push(Header {
+                        key,
+                        value,
+                        key_span,
+                        value_span,
+                    });
                 }
             }
             Token::BlankLine => {
                 iter.next();
                 break;
             }
-            Token::Separator | Token::Comment(_) | Token::Method(_) | Token::Variable { .. } => {
+            Token::Separator
+            | Token::Comment(_)
+            | Token::Directive { .. }
+            | Token::Method(_)
+            | Token::Variable { .. }
+            | Token::Assertion(_)
+            | Token::Output { .. } => {
                 break;
             }
             _ => {
@@ -179,59 +428,323 @@ fn parse_headers(iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>
     headers
 }
 
-fn parse_body(iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>) -> Option<String> {
-    let mut body_lines = Vec::new();
+fn parse_body(iter: &mut TokenIter) -> Vec<(String, Span)> {
+    let mut lines = Vec::new();
     while let Some(lt) = iter.peek() {
         match &lt.token {
             Token::BodyLine(_) => {
                 let lt = iter.next().unwrap();
                 if let Token::BodyLine(line) = lt.token {
-                    body_lines.push(line);
+                    lines.push((line, lt.span));
                 }
             }
+            // Blank lines belong to the body when more body text follows.
             Token::BlankLine => {
-                let next = iter.peek();
-                if let Some(nlt) = next {
-                    match &nlt.token {
-                        Token::BodyLine(_) => {
-                            iter.next();
-                            body_lines.push(String::new());
+                let continues = iter
+                    .clone()
+                    .find(|t| t.token != Token::BlankLine)
+                    .is_some_and(|t| matches!(t.token, Token::BodyLine(_)));
+                if !continues {
+                    break;
+                }
+                let lt = iter.next().unwrap();
+                if !lines.is_empty() {
+                    lines.push((String::new(), lt.span));
+                }
+            }
+            _ => break,
+        }
+    }
+    lines
+}
+
+/// The body made of `lines`, and its span: multipart parts if it starts with a
+/// `--- name` line, form fields if every line starts with `&`, a file for
+/// `< path`, or else the text itself.
+fn to_body(
+    lines: &[(String, Span)],
+    headers: &[Header],
+) -> Result<Option<(Body, Span)>, ReqxError> {
+    let (Some((first, first_span)), Some((_, last_span))) = (lines.first(), lines.last()) else {
+        return Ok(None);
+    };
+    let span = first_span.to(*last_span);
+    let content_type = headers
+        .iter()
+        .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.to_ascii_lowercase());
+    let multipart = part_name(first).is_some()
+        && content_type
+            .as_ref()
+            .is_none_or(|c| c.starts_with("multipart/form-data"));
+    let form = lines
+        .iter()
+        .all(|(line, _)| line.trim().is_empty() || line.trim_start().starts_with('&'))
+        && content_type.is_none_or(|c| c.starts_with("application/x-www-form-urlencoded"));
+    let body = if multipart {
+        Body::Multipart(parse_parts(lines)?)
+    } else if form {
+        Body::Form(parse_form(lines)?)
+    } else {
+        text_or_file(join_lines(lines), span)?
+    };
+    Ok(Some((body, span)))
+}
+
+/// The method that sends a GraphQL request, as `POST`.
+const GRAPHQL: &str = "GRAPHQL";
+
+const GRAPHQL_HELP: &str = "write the GraphQL document below the headers, optionally followed by a JSON object of variables";
+
+/// Remove an `X-Request-Type: GraphQL` header, which marks a GraphQL request
+/// instead of being sent.
+fn take_graphql_header(headers: &mut Vec<Header>) -> bool {
+    let len = headers.len();
+    headers.retain(|h| {
+        !(h.key.eq_ignore_ascii_case("x-request-type") && h.value.eq_ignore_ascii_case(GRAPHQL))
+    });
+    headers.len() < len
+}
+
+/// The body of a GraphQL request: the document, then any variables.
+fn to_graphql(lines: &[(String, Span)], request_span: Span) -> Result<(Body, Span), ReqxError> {
+    let (Some((_, first)), Some((_, last))) = (lines.first(), lines.last()) else {
+        return Err(parse_error(
+            "a GraphQL request needs a query".to_string(),
+            Some(GRAPHQL_HELP),
+            request_span,
+        ));
+    };
+    let span = first.to(*last);
+    let text = join_lines(lines);
+    let (query, variables) = match graphql_variables(&text) {
+        Some(start) => (
+            text[..start].trim_end(),
+            Some((
+                text[start..].to_string(),
+                span.subspan(&text, start..text.len()),
+            )),
+        ),
+        None => (text.as_str(), None),
+    };
+    let body = Body::Graphql {
+        query: query.to_string(),
+        variables,
+    };
+    Ok((body, span))
+}
+
+/// Where the JSON variables start in a GraphQL body: at a top-level `{` right
+/// after the `}` closing the operation, as in `query { user { id } } {"id": 1}`.
+/// Braces in strings and `#` comments do not count.
+fn graphql_variables(text: &str) -> Option<usize> {
+    let (mut depth, mut previous) = (0usize, None);
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '#' => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                continue;
+            }
+            '"' if text[i..].starts_with("\"\"\"") => {
+                let end = text[i + 3..]
+                    .find("\"\"\"")
+                    .map_or(text.len(), |e| i + 6 + e);
+                while chars.next_if(|&(j, _)| j < end).is_some() {}
+            }
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
                         }
-                        _ => break,
+                        '"' | '\n' => break,
+                        _ => {}
                     }
-                } else {
-                    break;
                 }
             }
-            Token::Separator | Token::Comment(_) | Token::Method(_) | Token::Variable { .. } => {
+            '{' if depth == 0 && previous == Some('}') => return Some(i),
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() => continue,
+            _ => {}
+        }
+        if depth == 0 {
+            previous = Some(c);
+        }
+    }
+    None
+}
+
+/// The `&key=value` fields of a form body.
+fn parse_form(lines: &[(String, Span)]) -> Result<Vec<Param>, ReqxError> {
+    lines
+        .iter()
+        .filter_map(|(line, span)| {
+            let trimmed = line.trim();
+            let field = trimmed.strip_prefix('&')?;
+            let start = line.len() - line.trim_start().len() + 1;
+            Some(to_param(
+                field,
+                span.subspan(line, start..start + field.len()),
+            ))
+        })
+        .collect()
+}
+
+fn join_lines(lines: &[(String, Span)]) -> String {
+    lines
+        .iter()
+        .map(|(line, _)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The name in a `--- name` line, which starts a multipart part.
+fn part_name(line: &str) -> Option<&str> {
+    let name = line.trim().strip_prefix("--- ")?.trim();
+    (!name.is_empty()).then_some(name)
+}
+
+const MULTIPART_HELP: &str = "start each part with `--- name`, then optional `Key: Value` part headers and a blank line, then the part's text or `< ./file`";
+
+/// The parts of a multipart body, whose first line is a `--- name` line.
+fn parse_parts(lines: &[(String, Span)]) -> Result<Vec<Part>, ReqxError> {
+    let mut parts = Vec::new();
+    let mut rest = lines;
+    while let Some(((marker, span), after)) = rest.split_first() {
+        let name = part_name(marker).unwrap_or_default();
+        if name.contains('"') {
+            return Err(parse_error(
+                format!("invalid part name `{}`", name),
+                Some(MULTIPART_HELP),
+                *span,
+            ));
+        }
+        let len = after
+            .iter()
+            .position(|(line, _)| part_name(line).is_some())
+            .unwrap_or(after.len());
+        let (mut content, next) = after.split_at(len);
+        rest = next;
+        while let Some(((line, _), init)) = content.split_last() {
+            if !line.trim().is_empty() {
                 break;
             }
-            _ => break,
+            content = init;
+        }
+
+        // Part headers are the lines before the first blank line, if they all
+        // look like headers.
+        let mut headers = Vec::new();
+        if let Some(blank) = content.iter().position(|(line, _)| line.trim().is_empty()) {
+            let parsed: Option<Vec<Header>> = content[..blank]
+                .iter()
+                .map(|(line, span)| to_header(line, *span))
+                .collect();
+            if let Some(parsed) = parsed.filter(|h| !h.is_empty()) {
+                headers = parsed;
+                content = &content[blank + 1..];
+            }
         }
+
+        let body_span = match (content.first(), content.last()) {
+            (Some((_, first)), Some((_, last))) => first.to(*last),
+            _ => *span,
+        };
+        parts.push(Part {
+            name: name.to_string(),
+            headers,
+            body: text_or_file(join_lines(content), body_span)?,
+            span: *span,
+            body_span,
+        });
     }
+    Ok(parts)
+}
 
-    if body_lines.is_empty() {
-        None
-    } else {
-        Some(body_lines.join("\n"))
+/// A `Key: Value` part header.
+fn to_header(line: &str, span: Span) -> Option<Header> {
+    let (key, value) = line.split_once(':')?;
+    let key_start = key.len() - key.trim_start().len();
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    let value_start = line.len() - value.trim_start().len();
+    let value = value.trim();
+    Some(Header {
+        key: key.to_string(),
+        value: value.to_string(),
+        key_span: span.subspan(line, key_start..key_start + key.len()),
+        value_span: span.subspan(line, value_start..value_start + value.len()),
+    })
+}
+
+/// A body read from a file with `< path` or `<@ path`, or else the text itself.
+/// `<` must be followed by whitespace, so XML and HTML bodies stay text.
+fn text_or_file(text: String, span: Span) -> Result<Body, ReqxError> {
+    let Some(rest) = text.trim_start().strip_prefix('<') else {
+        return Ok(Body::Text(text));
+    };
+    let (interpolate, rest) = match rest.strip_prefix('@') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    if !rest.starts_with(char::is_whitespace) {
+        return Ok(Body::Text(text));
+    }
+    let path = rest.trim();
+    if path.is_empty() || path.contains('\n') {
+        return Err(parse_error(
+            "a `< path` body must be a single line with the file path".to_string(),
+            Some(BODY_FILE_HELP),
+            span,
+        ));
     }
+    Ok(Body::File {
+        path: path.to_string(),
+        interpolate,
+    })
+}
+
+const BODY_FILE_HELP: &str = "write `< ./payload.json` to send a file as the body, or `<@ ./template.json` to interpolate `{{variables}}` in it first";
+
+/// The lines after a request's body.
+#[derive(Default)]
+struct Trailer {
+    extracts: Vec<Extract>,
+    assertions: Vec<Assertion>,
+    output: Option<Output>,
 }
 
-fn parse_extracts(
-    iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>,
-) -> Vec<Variable> {
-    let mut extracts = Vec::new();
+/// Extractions (`@name = path`), assertions (`? ...`) and an output redirect
+/// (`> path`) after the body, in any order.
+fn parse_trailer(iter: &mut TokenIter) -> Result<Trailer, ReqxError> {
+    let mut trailer = Trailer::default();
     while let Some(lt) = iter.peek() {
         match &lt.token {
             Token::Variable { .. } => {
                 let lt = iter.next().unwrap();
-                if let Token::Variable { name, value } = lt.token {
-                    extracts.push(Variable {
-                        name,
-                        value,
-                        line: lt.line,
-                    });
+                trailer.extracts.push(parse_extract(to_variable(lt))?);
+            }
+            Token::Assertion(_) => {
+                let lt = iter.next().unwrap();
+                trailer.assertions.push(parse_assertion(lt)?);
+            }
+            Token::Output { .. } => {
+                let lt = iter.next().unwrap();
+                if let Some(previous) = &trailer.output {
+                    return Err(parse_error(
+                        format!(
+                            "the response is already saved to `{}` on line {}",
+                            previous.path, previous.span.line
+                        ),
+                        Some("a request can have only one `>` or `>>` output"),
+                        lt.span,
+                    ));
                 }
+                trailer.output = Some(to_output(lt));
             }
             Token::BlankLine => {
                 iter.next();
@@ -239,34 +752,255 @@ fn parse_extracts(
             _ => break,
         }
     }
-    extracts
+    Ok(trailer)
+}
+
+/// Convert a `Token::Output` into its AST node.
+fn to_output(lt: LocatedToken) -> Output {
+    let path_span = lt.value_span.unwrap_or(lt.span);
+    match lt.token {
+        Token::Output { path, append } => Output {
+            path,
+            append,
+            span: lt.span,
+            path_span,
+        },
+        other => unreachable!("expected an output token, found {:?}", other),
+    }
+}
+
+const EXTRACT_HELP: &str = "extractions look like `@id = user.id`, `@id = $.items[0].id`, `@loc = header Location`, `@code = status`, `@sid = cookie session_id`, `@csrf = regex value=\"([^\"]+)\"`, `@id = xpath //user/@id` or `@title = css h1.title`";
+
+/// Turn an `@name = source` line after a request into an extraction.
+fn parse_extract(var: Variable) -> Result<Extract, ReqxError> {
+    let (name, optional) = match var.name.strip_suffix('?') {
+        Some(name) => (name.trim_end().to_string(), true),
+        None => (var.name, false),
+    };
+    let text = var.value;
+    let span = var.value_span;
+    let error = |message: String| parse_error(message, Some(EXTRACT_HELP), span);
+
+    let check_xpath = |expr: String| match markup::compile_xpath(&expr) {
+        Ok(_) => Ok(expr),
+        Err(message) => Err(error(message)),
+    };
+
+    let (keyword, rest) = text
+        .split_once(char::is_whitespace)
+        .map_or((text.as_str(), ""), |(k, r)| (k, r.trim()));
+    let argument = |what: &str| {
+        if rest.is_empty() {
+            Err(error(format!("`{}` needs {}", keyword, what)))
+        } else {
+            Ok(rest.to_string())
+        }
+    };
+    let source = match keyword {
+        "" => return Err(error(format!("`@{}` has nothing to extract", name))),
+        "status" if rest.is_empty() => ExtractSource::Status,
+        "header" => ExtractSource::Header(argument("a header name")?),
+        "cookie" => ExtractSource::Cookie(argument("a cookie name")?),
+        "regex" => {
+            let pattern = argument("a pattern")?;
+            if let Err(e) = regex::Regex::new(&pattern) {
+                return Err(error(format!("invalid regex `{}`: {}", pattern, e)));
+            }
+            ExtractSource::Regex(pattern)
+        }
+        "xpath" => ExtractSource::XPath(check_xpath(argument("an XPath expression")?)?),
+        "css" => {
+            let argument = argument("a CSS selector")?;
+            // `css a.next @href`: `@` cannot appear in a selector.
+            let (selector, attribute) = match argument.rsplit_once('@') {
+                Some((selector, attribute)) if !attribute.trim().is_empty() => {
+                    (selector.trim(), Some(attribute.trim().to_string()))
+                }
+                _ => (argument.as_str(), None),
+            };
+            if let Err(message) = markup::parse_selector(selector) {
+                return Err(error(message));
+            }
+            ExtractSource::Css {
+                selector: selector.to_string(),
+                attribute,
+            }
+        }
+        _ if text.starts_with('/') => ExtractSource::XPath(check_xpath(text.clone())?),
+        _ => {
+            check_json_path(&text, span)?;
+            ExtractSource::JsonPath(text.clone())
+        }
+    };
+
+    Ok(Extract {
+        name,
+        source,
+        text,
+        optional,
+        line: var.line,
+        span: var.span,
+        value_span: span,
+    })
+}
+
+/// Check a JSON path while parsing, so a typo is reported before anything is sent.
+fn check_json_path(path: &str, span: Span) -> Result<(), ReqxError> {
+    jsonpath::Query::parse(path).map(|_| ()).map_err(|e| {
+        let span = match e.position {
+            Some(pos) => span.subspan(path, pos.min(path.len())..pos.min(path.len())),
+            None => span,
+        };
+        parse_error(e.message, Some(jsonpath::JSONPATH_HELP), span)
+    })
+}
+
+const ASSERTION_HELP: &str = "assertions look like `? status == 200`, `? header Content-Type contains json`, `? body.user.id == 42` or `? duration < 500ms`";
+
+/// The next whitespace-delimited word in `text` at or after `pos`, as a byte range.
+fn next_word(text: &str, pos: usize) -> Option<(usize, usize)> {
+    let start = pos + text[pos..].find(|c: char| !c.is_whitespace())?;
+    let end = text[start..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| start + i);
+    Some((start, end))
+}
+
+/// Parse `subject operator expected` from a `Token::Assertion`.
+fn parse_assertion(lt: LocatedToken) -> Result<Assertion, ReqxError> {
+    let Token::Assertion(text) = lt.token else {
+        unreachable!("expected an assertion token");
+    };
+    let text_span = lt.value_span.unwrap_or(lt.span);
+    let error = |message: String, range: std::ops::Range<usize>| {
+        parse_error(
+            message,
+            Some(ASSERTION_HELP),
+            text_span.subspan(&text, range),
+        )
+    };
+
+    let (start, end) =
+        next_word(&text, 0).ok_or_else(|| error("empty assertion".to_string(), 0..0))?;
+    let word = &text[start..end];
+    let (subject, mut pos) = match word {
+        "status" => (AssertionSubject::Status, end),
+        "duration" => (AssertionSubject::Duration, end),
+        "body" => (AssertionSubject::Body(None), end),
+        "header" => {
+            let (name_start, name_end) = next_word(&text, end)
+                .ok_or_else(|| error("expected a header name".to_string(), end..end))?;
+            (
+                AssertionSubject::Header(text[name_start..name_end].to_string()),
+                name_end,
+            )
+        }
+        _ => match word.strip_prefix("body.") {
+            Some(path) if !path.is_empty() => {
+                check_json_path(path, text_span.subspan(&text, start + 5..end))?;
+                (AssertionSubject::Body(Some(path.to_string())), end)
+            }
+            _ => {
+                return Err(error(
+                    format!("unknown assertion subject `{}`", word),
+                    start..end,
+                ))
+            }
+        },
+    };
+
+    let (op_start, op_end) = next_word(&text, pos)
+        .ok_or_else(|| error("expected a comparison operator".to_string(), pos..pos))?;
+    let operator = text[op_start..op_end]
+        .parse::<AssertionOperator>()
+        .map_err(|_| {
+            error(
+                format!("unknown operator `{}`", &text[op_start..op_end]),
+                op_start..op_end,
+            )
+        })?;
+    pos = op_end;
+
+    let expected_start = next_word(&text, pos).map_or(text.len(), |(s, _)| s);
+    let raw_expected = text[expected_start..].trim_end();
+    let expected_range = expected_start..expected_start + raw_expected.len();
+    if operator == AssertionOperator::Exists {
+        if !raw_expected.is_empty() {
+            return Err(error(
+                "`exists` does not take a value".to_string(),
+                expected_range,
+            ));
+        }
+    } else if raw_expected.is_empty() {
+        return Err(error(
+            format!("expected a value after `{}`", operator),
+            pos..pos,
+        ));
+    }
+
+    // `"quoted"` values compare without their quotes.
+    let expected = raw_expected
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(raw_expected)
+        .to_string();
+
+    Ok(Assertion {
+        subject,
+        operator,
+        expected,
+        expected_span: text_span.subspan(&text, expected_range),
+        text,
+        line: lt.line,
+        span: lt.span,
+    })
 }
 
 fn parse_request(
-    iter: &mut std::iter::Peekable<std::vec::IntoIter<LocatedToken>>,
-) -> Result<Request, String> {
-    let comment = parse_comment(iter);
-    let (method_opt, url, line) = parse_method_and_url(iter)?;
-    let headers = parse_headers(iter);
-    let body = parse_body(iter);
-    let extracts = parse_extracts(iter);
-
-    let method = method_opt.unwrap_or_else(|| {
+    iter: &mut TokenIter,
+    names: &HashMap<String, usize>,
+) -> Result<Request, ReqxError> {
+    let first_span = iter.peek().map(|lt| lt.span).unwrap_or_default();
+    let preamble = parse_preamble(iter, names)?;
+    let request_line = parse_method_and_url(iter, first_span)?;
+    let query = parse_query(iter)?;
+    let mut headers = parse_headers(iter);
+    let graphql = request_line.graphql | take_graphql_header(&mut headers);
+    let lines = parse_body(iter);
+    let body = match graphql {
+        true => Some(to_graphql(&lines, request_line.span)?),
+        false => to_body(&lines, &headers)?,
+    };
+    let trailer = parse_trailer(iter)?;
+
+    let method = request_line.method.unwrap_or_else(|| {
         if body.is_some() {
             HttpMethod::Post
         } else {
             HttpMethod::Get
         }
     });
+    let (body, body_span) = match body {
+        Some((body, span)) => (Some(body), Some(span)),
+        None => (None, None),
+    };
 
     Ok(Request {
-        comment,
+        comment: preamble.comment,
+        name: preamble.name,
         method,
-        url,
+        url: request_line.url,
+        query,
         headers,
         body,
-        extracts,
-        line,
+        no_interpolate: preamble.no_interpolate,
+        extracts: trailer.extracts,
+        assertions: trailer.assertions,
+        output: trailer.output,
+        line: request_line.line,
+        span: request_line.span,
+        url_span: request_line.url_span,
+        body_span,
     })
 }
 
@@ -317,12 +1051,158 @@ Content-Type: application/json
         let file = parse(tokens).unwrap();
         assert_eq!(file.requests.len(), 1);
         assert_eq!(file.requests[0].method, HttpMethod::Post);
-        assert!(file.requests[0].body.is_some());
-        let body = file.requests[0].body.as_ref().unwrap();
+        let Some(Body::Text(body)) = &file.requests[0].body else {
+            panic!("expected a text body");
+        };
         assert!(body.contains("\"name\""));
         assert!(body.contains("Test User"));
     }
 
+    #[test]
+    fn test_parse_body_file() {
+        let input = "POST :3000/a\n\n< ./fixtures/user.json\n###\nPOST :3000/b\n\n<@  ./t.json \n###\nPOST :3000/c\n\n<note/>";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let bodies: Vec<_> = file.requests.iter().map(|r| r.body.clone()).collect();
+        assert_eq!(
+            bodies,
+            vec![
+                Some(Body::File {
+                    path: "./fixtures/user.json".to_string(),
+                    interpolate: false
+                }),
+                Some(Body::File {
+                    path: "./t.json".to_string(),
+                    interpolate: true
+                }),
+                Some(Body::Text("<note/>".to_string())),
+            ]
+        );
+
+        let err = parse(tokenize("POST :3000\n\n< a.json\n{}").unwrap()).unwrap_err();
+        assert!(err.message().contains("single line"));
+    }
+
+    #[test]
+    fn test_parse_query_and_form() {
+        let input = "POST :3000/search\n    ?q = {{term}}\n    &debug\nAccept: */*\n\n&name=Jane Doe\n&city={{city}}";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let req = &file.requests[0];
+        let pairs: Vec<_> = req
+            .query
+            .iter()
+            .map(|p| (p.key.as_str(), p.value.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("q", "{{term}}"), ("debug", "")]);
+        assert_eq!(req.headers.len(), 1);
+        let span = req.query[0].value_span;
+        assert_eq!((span.line, span.column), (2, 10));
+
+        let Some(Body::Form(fields)) = &req.body else {
+            panic!("expected a form body, got {:?}", req.body);
+        };
+        assert_eq!(fields[0].key, "name");
+        assert_eq!(fields[0].value, "Jane Doe");
+        assert_eq!(fields[1].value, "{{city}}");
+        assert_eq!((fields[1].key_span.line, fields[1].key_span.column), (7, 2));
+
+        // With another Content-Type, `&` lines are just text.
+        let input = "POST :3000\nContent-Type: text/plain\n\n&a=1";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        assert_eq!(file.requests[0].body, Some(Body::Text("&a=1".to_string())));
+
+        let err = parse(tokenize("GET :3000\n  ?=1").unwrap()).unwrap_err();
+        assert_eq!(err.message(), "missing parameter name in `=1`");
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let input = "POST :3000\n\n{}\n\n> ./out.json\n@id = id";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let req = &file.requests[0];
+        assert_eq!(req.body, Some(Body::Text("{}".to_string())));
+        assert_eq!(req.extracts.len(), 1);
+        let output = req.output.as_ref().unwrap();
+        assert_eq!(output.path, "./out.json");
+        assert!(!output.append);
+        assert_eq!((output.path_span.line, output.path_span.column), (5, 3));
+
+        let err = parse(tokenize("GET :3000\n> a.json\n>> b.json").unwrap()).unwrap_err();
+        assert_eq!(
+            err.message(),
+            "the response is already saved to `a.json` on line 2"
+        );
+    }
+
+    #[test]
+    fn test_parse_graphql() {
+        let input = "GRAPHQL :3000/graphql\n\n# find {\nquery ($id: ID!) {\n  user(id: $id, note: \"}{\") { name }\n}\n\n{\"id\": {{id}}}";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let req = &file.requests[0];
+        assert_eq!(req.method, HttpMethod::Post);
+        let Some(Body::Graphql { query, variables }) = &req.body else {
+            panic!("expected a GraphQL body, got {:?}", req.body);
+        };
+        assert!(query.starts_with("# find {") && query.ends_with("{ name }\n}"));
+        let (variables, span) = variables.as_ref().unwrap();
+        assert_eq!(variables, "{\"id\": {{id}}}");
+        assert_eq!((span.line, span.column), (8, 1));
+
+        let input = "PUT :3000/graphql\nX-Request-Type: graphql\nAccept: */*\n\n{ me { id } }";
+        let req = &parse(tokenize(input).unwrap()).unwrap().requests[0];
+        assert_eq!(req.method, HttpMethod::Put);
+        assert_eq!(req.headers.len(), 1);
+        assert_eq!(
+            req.body,
+            Some(Body::Graphql {
+                query: "{ me { id } }".to_string(),
+                variables: None
+            })
+        );
+
+        let err = parse(tokenize("GRAPHQL :3000/graphql").unwrap()).unwrap_err();
+        assert_eq!(err.message(), "a GraphQL request needs a query");
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        let input = "POST :3000/photos\n\n--- title\nBeach\n\nday\n\n--- photo\nContent-Type: image/png\n\n< ./beach.png\n\n? status == 201";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let req = &file.requests[0];
+        let Some(Body::Multipart(parts)) = &req.body else {
+            panic!("expected a multipart body, got {:?}", req.body);
+        };
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, "title");
+        assert_eq!(parts[0].body, Body::Text("Beach\n\nday".to_string()));
+        assert!(parts[0].headers.is_empty());
+        assert_eq!(parts[1].name, "photo");
+        assert_eq!(parts[1].headers[0].key, "Content-Type");
+        assert_eq!(parts[1].headers[0].value, "image/png");
+        assert_eq!(
+            (
+                parts[1].headers[0].value_span.line,
+                parts[1].headers[0].value_span.column
+            ),
+            (9, 15)
+        );
+        assert_eq!(
+            parts[1].body,
+            Body::File {
+                path: "./beach.png".to_string(),
+                interpolate: false
+            }
+        );
+        assert_eq!(req.assertions.len(), 1);
+
+        // With another Content-Type, `---` is just text.
+        let input = "POST :3000\nContent-Type: text/plain\n\n--- a\nb";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        assert_eq!(
+            file.requests[0].body,
+            Some(Body::Text("--- a\nb".to_string()))
+        );
+    }
+
     #[test]
     fn test_parse_multiple_requests() {
         let input = r#"# First request
@@ -367,4 +1247,277 @@ Content-Type: application/json
         assert_eq!(file.requests[1].url, ":3000/users");
         assert!(file.requests[1].body.is_some());
     }
+
+    #[test]
+    fn test_parse_error_location() {
+        let input = "GET https://api.example.com\n\n###\n\nPOST\nContent-Type: text/plain";
+        let tokens = tokenize(input).unwrap();
+        match parse(tokens).unwrap_err() {
+            ReqxError::Parse {
+                message,
+                help,
+                location,
+            } => {
+                assert!(help.unwrap().contains("same line"));
+                assert!(message.contains("expected URL"));
+                assert_eq!(location.span.line, 5);
+                assert_eq!(location.span.column, 5);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_recovers_at_separator() {
+        let input = r#"# Broken: method without URL
+POST
+Content-Type: application/json
+
+###
+
+GET https://api.example.com/ok
+
+###
+
+# Broken: header before the request line
+Accept: */*
+GET https://api.example.com/never
+
+###
+
+DELETE
+###
+:3000/also-ok"#;
+        let tokens = tokenize(input).unwrap();
+        let output = parse_recovering(tokens);
+
+        let lines: Vec<usize> = output
+            .errors
+            .iter()
+            .map(|e| e.location().unwrap().span.line)
+            .collect();
+        assert_eq!(lines, vec![2, 12, 17]);
+
+        let urls: Vec<&str> = output
+            .file
+            .requests
+            .iter()
+            .map(|r| r.url.as_str())
+            .collect();
+        assert_eq!(urls, vec!["https://api.example.com/ok", ":3000/also-ok"]);
+    }
+
+    #[test]
+    fn test_parse_assertions() {
+        let input = r#"POST https://api.example.com/users
+
+{"name": "test"}
+
+? status == 201
+@uid = id
+? header Content-Type contains json
+? body.user.name == "Test User"
+? body exists
+? duration < 500ms"#;
+        let tokens = tokenize(input).unwrap();
+        let file = parse(tokens).unwrap();
+        let req = &file.requests[0];
+        assert_eq!(req.extracts.len(), 1);
+
+        let parsed: Vec<(&AssertionSubject, AssertionOperator, &str)> = req
+            .assertions
+            .iter()
+            .map(|a| (&a.subject, a.operator, a.expected.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                (&AssertionSubject::Status, AssertionOperator::Eq, "201"),
+                (
+                    &AssertionSubject::Header("Content-Type".to_string()),
+                    AssertionOperator::Contains,
+                    "json"
+                ),
+                (
+                    &AssertionSubject::Body(Some("user.name".to_string())),
+                    AssertionOperator::Eq,
+                    "Test User"
+                ),
+                (&AssertionSubject::Body(None), AssertionOperator::Exists, ""),
+                (&AssertionSubject::Duration, AssertionOperator::Lt, "500ms"),
+            ]
+        );
+        let status = &req.assertions[0];
+        assert_eq!(
+            &input[status.expected_span.start..status.expected_span.end],
+            "201"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_assertion() {
+        let input = "GET https://api.example.com\n? status ~= 200";
+        let tokens = tokenize(input).unwrap();
+        let err = parse(tokens).unwrap_err();
+        assert!(err.message().contains("unknown operator `~=`"));
+        assert_eq!(err.location().unwrap().span.column, 10);
+    }
+
+    #[test]
+    fn test_parse_named_requests() {
+        let input = r#"@host = https://api.example.com
+
+# Log in
+# @name login
+POST {{host}}/login
+
+###
+
+# @name me
+{{host}}/me"#;
+        let tokens = tokenize(input).unwrap();
+        let file = parse(tokens).unwrap();
+        assert_eq!(file.variables.len(), 1);
+        let names: Vec<Option<&str>> = file.requests.iter().map(|r| r.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("login"), Some("me")]);
+        assert_eq!(file.requests[0].comment, Some("Log in".to_string()));
+    }
+
+    #[test]
+    fn test_parse_duplicate_request_name() {
+        let input = "# @name login\nGET :3000/a\n###\n# @name login\nGET :3000/b\n###\n# @name other\nGET :3000/c";
+        let tokens = tokenize(input).unwrap();
+        let output = parse_recovering(tokens);
+        assert_eq!(output.errors.len(), 1);
+        let err = &output.errors[0];
+        assert!(err.message().contains("duplicate request name `login`"));
+        assert!(err.help().unwrap().contains("line 2"));
+        assert_eq!(err.location().unwrap().span.line, 4);
+        assert_eq!(output.file.requests.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_invalid_directive() {
+        let err = parse(tokenize("# @name log.in\nGET :3000").unwrap()).unwrap_err();
+        assert!(err.message().contains("invalid request name"));
+        let err = parse(tokenize("# @nmae login\nGET :3000").unwrap()).unwrap_err();
+        assert!(err.message().contains("unknown directive `@nmae`"));
+        let err = parse(tokenize("# @no-interpolate yes\nGET :3000").unwrap()).unwrap_err();
+        assert_eq!(err.message(), "`@no-interpolate` takes no value");
+    }
+
+    #[test]
+    fn test_parse_no_interpolate() {
+        let input = "# @no-interpolate\nPOST :3000/render\n\n{{title}}\n###\nPOST :3000\n\n{{x}}";
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        assert!(file.requests[0].no_interpolate);
+        assert!(!file.requests[1].no_interpolate);
+    }
+
+    #[test]
+    fn test_parse_extract_sources() {
+        let input = r#"GET https://api.example.com
+
+@id = user.id
+@loc = header Location
+@code = status
+@sid? = cookie session_id
+@csrf = regex name="csrf" value="([^"]+)""#;
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let extracts: Vec<(&str, &ExtractSource, bool)> = file.requests[0]
+            .extracts
+            .iter()
+            .map(|e| (e.name.as_str(), &e.source, e.optional))
+            .collect();
+        assert_eq!(
+            extracts,
+            vec![
+                ("id", &ExtractSource::JsonPath("user.id".to_string()), false),
+                ("loc", &ExtractSource::Header("Location".to_string()), false),
+                ("code", &ExtractSource::Status, false),
+                (
+                    "sid",
+                    &ExtractSource::Cookie("session_id".to_string()),
+                    true
+                ),
+                (
+                    "csrf",
+                    &ExtractSource::Regex(r#"name="csrf" value="([^"]+)""#.to_string()),
+                    false
+                ),
+            ]
+        );
+
+        let err = parse(tokenize("GET https://a.com\n@x = regex ([a-z\n").unwrap()).unwrap_err();
+        assert!(err.message().starts_with("invalid regex"));
+        let err = parse(tokenize("GET https://a.com\n@x = header").unwrap()).unwrap_err();
+        assert_eq!(err.message(), "`header` needs a header name");
+    }
+
+    #[test]
+    fn test_parse_markup_extract_sources() {
+        let input = r#"GET https://api.example.com
+
+@id = xpath //user/@id
+@name = /users/user[1]/name
+@title = css h1.title
+@next = css nav a.next @href"#;
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let sources: Vec<&ExtractSource> = file.requests[0]
+            .extracts
+            .iter()
+            .map(|e| &e.source)
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                &ExtractSource::XPath("//user/@id".to_string()),
+                &ExtractSource::XPath("/users/user[1]/name".to_string()),
+                &ExtractSource::Css {
+                    selector: "h1.title".to_string(),
+                    attribute: None
+                },
+                &ExtractSource::Css {
+                    selector: "nav a.next".to_string(),
+                    attribute: Some("href".to_string())
+                },
+            ]
+        );
+
+        let err = parse(tokenize("GET https://a.com\n@x = xpath //user[").unwrap()).unwrap_err();
+        assert!(err.message().starts_with("invalid XPath `//user[`"));
+        let err = parse(tokenize("GET https://a.com\n@x = css h1..title").unwrap()).unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("invalid CSS selector `h1..title`"));
+    }
+
+    #[test]
+    fn test_parse_invalid_json_paths() {
+        let input = "GET https://api.example.com\n\n@id = $.items[?@.id ==]";
+        let err = parse(tokenize(input).unwrap()).unwrap_err();
+        assert!(err
+            .message()
+            .starts_with("invalid JSONPath `$.items[?@.id ==]`"));
+        assert_eq!(err.location().unwrap().span.line, 3);
+
+        let input = "GET https://api.example.com\n? body.$.items[ == 1";
+        let err = parse(tokenize(input).unwrap()).unwrap_err();
+        assert!(err.message().starts_with("invalid JSONPath"));
+        assert!(err.help().unwrap().contains("$.items[0].id"));
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let input = "POST https://{{host}}/users\nX-Token: {{token}}\n\n{\"a\": 1}";
+        let tokens = tokenize(input).unwrap();
+        let file = parse(tokens).unwrap();
+        let req = &file.requests[0];
+        let text = |span: Span| &input[span.start..span.end];
+        assert_eq!(text(req.span), "POST https://{{host}}/users");
+        assert_eq!(text(req.url_span), "https://{{host}}/users");
+        assert_eq!(text(req.headers[0].key_span), "X-Token");
+        assert_eq!(text(req.headers[0].value_span), "{{token}}");
+        assert_eq!(text(req.body_span.unwrap()), "{\"a\": 1}");
+    }
 }
@@ -0,0 +1,129 @@
+//! Request references: `{{login.response.body.$.token}}` reads the response
+//! of the request named `login`.
+
+use crate::client::HttpResponse;
+use crate::jsonpath;
+use serde_json::Value;
+
+/// `name.response.<part>`, split into its pieces.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Reference<'a> {
+    pub request: &'a str,
+    pub part: ResponsePart<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ResponsePart<'a> {
+    /// `status`
+    Status,
+    /// `headers.Location`
+    Header(&'a str),
+    /// `body` (the raw body) or `body.$.token` (a JSON path)
+    Body(Option<&'a str>),
+}
+
+pub(crate) const REFERENCE_HELP: &str = "references look like `{{name.response.status}}`, `{{name.response.headers.Location}}` or `{{name.response.body.$.token}}`";
+
+/// Parse `expr` as a request reference.
+///
+/// Returns `None` if `expr` does not have the `name.response` shape at all,
+/// and an error message if it does but the response part is invalid.
+pub(crate) fn parse(expr: &str) -> Option<Result<Reference<'_>, String>> {
+    let (request, rest) = expr.split_once('.')?;
+    let part = match rest.strip_prefix("response") {
+        Some("") => "",
+        Some(part) => part.strip_prefix('.')?,
+        None => return None,
+    };
+
+    let part = match part {
+        "status" => Ok(ResponsePart::Status),
+        "body" => Ok(ResponsePart::Body(None)),
+        _ => {
+            if let Some(header) = part.strip_prefix("headers.").filter(|h| !h.is_empty()) {
+                Ok(ResponsePart::Header(header))
+            } else if let Some(path) = part.strip_prefix("body.").filter(|p| !p.is_empty()) {
+                Ok(ResponsePart::Body(Some(path)))
+            } else if part.is_empty() {
+                Err(format!(
+                    "`{}` does not say which part of the response to use",
+                    expr
+                ))
+            } else {
+                Err(format!("unknown response part `{}` in `{}`", part, expr))
+            }
+        }
+    };
+    Some(part.map(|part| Reference { request, part }))
+}
+
+/// Read `part` from `response`, or say why it is missing.
+/// Body paths keep the type of what they select and the status is a number.
+pub(crate) fn resolve(part: &ResponsePart, response: &HttpResponse) -> Result<Value, String> {
+    match part {
+        ResponsePart::Status => Ok(Value::from(response.status)),
+        ResponsePart::Header(name) => response
+            .header(name)
+            .map(Value::from)
+            .ok_or_else(|| format!("the response has no `{}` header", name)),
+        ResponsePart::Body(None) => Ok(Value::String(response.text().into_owned())),
+        ResponsePart::Body(Some(path)) => {
+            let json: Value = serde_json::from_str(&response.text())
+                .map_err(|_| "the response body is not valid JSON".to_string())?;
+            jsonpath::select(&json, path)
+                .map_err(|e| e.message)?
+                .ok_or_else(|| format!("`{}` is not in the response body", path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference() {
+        let parsed = |expr| parse(expr).map(|r| r.map(|r| (r.request, r.part)));
+        assert_eq!(
+            parsed("login.response.status"),
+            Some(Ok(("login", ResponsePart::Status)))
+        );
+        assert_eq!(
+            parsed("login.response.headers.Location"),
+            Some(Ok(("login", ResponsePart::Header("Location"))))
+        );
+        assert_eq!(
+            parsed("login.response.body.$.token"),
+            Some(Ok(("login", ResponsePart::Body(Some("$.token")))))
+        );
+        assert_eq!(parsed("user.id"), None);
+        assert_eq!(parsed("login.responses.status"), None);
+        assert!(matches!(parsed("login.response.cookies"), Some(Err(_))));
+        assert!(matches!(parsed("login.response"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_resolve_reference() {
+        let response = HttpResponse {
+            status: 201,
+            status_is_success: true,
+            status_is_client_error: false,
+            status_is_server_error: false,
+            headers: vec![("location".to_string(), "/users/7".to_string())],
+            body: r#"{"token": "abc", "user": {"id": 7}}"#.into(),
+        };
+        let resolved = |part| resolve(&part, &response);
+        assert_eq!(resolved(ResponsePart::Status).unwrap(), 201);
+        assert_eq!(
+            resolved(ResponsePart::Header("Location")).unwrap(),
+            "/users/7"
+        );
+        assert_eq!(
+            resolved(ResponsePart::Body(Some("$.token"))).unwrap(),
+            "abc"
+        );
+        assert_eq!(resolved(ResponsePart::Body(Some("user.id"))).unwrap(), 7);
+        assert!(resolved(ResponsePart::Body(Some("$.missing"))).is_err());
+        assert!(resolved(ResponsePart::Header("Set-Cookie")).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Structured results produced by `interpreter::execute`.
+
+use crate::ast::HttpMethod;
+use crate::client::HttpResponse;
+use crate::error::ReqxError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The outcome of running a `.reqx` file.
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    /// Number of requests defined in the file (not only the ones that ran).
+    pub total: usize,
+    /// Every request that was executed, in execution order.
+    pub requests: Vec<RequestReport>,
+    /// Requests that failed with an error under `ExecuteOptions::keep_going`,
+    /// with their index, in the order they failed.
+    pub errors: Vec<(usize, ReqxError)>,
+    /// The variable map after the last request, including extracted values.
+    pub variables: HashMap<String, Value>,
+}
+
+impl ExecutionReport {
+    /// Whether no request failed with an error and every assertion passed.
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty() && self.requests.iter().all(RequestReport::passed)
+    }
+
+    /// Every failed assertion, with the index of its request.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, &AssertionResult)> {
+        self.requests.iter().flat_map(|r| {
+            r.assertions
+                .iter()
+                .filter(|a| !a.passed)
+                .map(move |a| (r.index, a))
+        })
+    }
+}
+
+/// Where the value of a global variable comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableOrigin {
+    /// A `VariableLayer` of `ExecuteOptions::environment`, such as `staging`.
+    Environment(String),
+    /// An `@name = value` line of the file.
+    File,
+    /// A `VariableLayer` of `ExecuteOptions::overrides`, such as `--var`.
+    Override(String),
+}
+
+/// The outcome of a single request.
+#[derive(Debug, Clone)]
+pub struct RequestReport {
+    /// Zero-based position of the request in the file.
+    pub index: usize,
+    pub comment: Option<String>,
+    pub name: Option<String>,
+    pub request: ResolvedRequest,
+    /// `None` when the request was not sent (dry-run).
+    pub response: Option<HttpResponse>,
+    /// Time spent waiting on the `HttpClient`, if the request was sent.
+    pub duration: Option<Duration>,
+    pub extractions: Vec<Extraction>,
+    pub assertions: Vec<AssertionResult>,
+    /// Files the response was written to, by a `> path` redirect or the
+    /// output directory.
+    pub saved: Vec<PathBuf>,
+}
+
+impl RequestReport {
+    /// Whether every assertion of this request passed.
+    pub fn passed(&self) -> bool {
+        self.assertions.iter().all(|a| a.passed)
+    }
+}
+
+/// A request after variable interpolation, exactly as handed to the `HttpClient`.
+#[derive(Debug, Clone)]
+pub struct ResolvedRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl ResolvedRequest {
+    /// The body as text, if there is one and it is valid UTF-8.
+    pub fn body_text(&self) -> Option<&str> {
+        std::str::from_utf8(self.body.as_deref()?).ok()
+    }
+}
+
+/// A variable extracted from a response: `@name = source`
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    pub name: String,
+    /// The source as written, e.g. `user.id` or `header Location`.
+    pub source: String,
+    /// `None` when an optional extraction found nothing.
+    pub value: Option<Value>,
+}
+
+/// The outcome of a `? subject operator expected` assertion.
+#[derive(Debug, Clone)]
+pub struct AssertionResult {
+    /// The assertion as written, e.g. `status == 201`, or `no GraphQL errors`
+    /// when the response to a GraphQL request has errors.
+    pub text: String,
+    pub line: usize,
+    /// The expected value, after variable interpolation.
+    pub expected: String,
+    /// The value found in the response, `None` if it was missing.
+    pub actual: Option<String>,
+    pub passed: bool,
+    /// Why the assertion failed. `None` when it passed.
+    pub message: Option<String>,
+}
@@ -0,0 +1,77 @@
+//! Event sink for `interpreter::execute`.
+//!
+//! The interpreter never prints anything itself. Instead it notifies a
+//! `Reporter` as the run progresses, so the same engine can drive coloured
+//! terminal output, machine-readable logs or a test runner.
+
+use crate::ast::Request;
+use crate::client::HttpResponse;
+use crate::error::ReqxError;
+use crate::report::{
+    AssertionResult, ExecutionReport, Extraction, RequestReport, ResolvedRequest, VariableOrigin,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Why a request was not sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Excluded by the request index or method filter.
+    NotSelected,
+    /// Resolved but not sent because of `dry_run`.
+    DryRun,
+}
+
+/// Receives events while a `ReqxFile` is being executed.
+///
+/// Every method has an empty default implementation, so implementors only
+/// override the events they care about.
+pub trait Reporter {
+    /// Called once, before any request, with the global variables and where
+    /// each of them comes from.
+    fn run_started(
+        &mut self,
+        _variables: &HashMap<String, Value>,
+        _origins: &HashMap<String, VariableOrigin>,
+    ) {
+    }
+
+    /// A selected request is about to be resolved. `index` is zero-based.
+    fn request_started(&mut self, _index: usize, _total: usize, _request: &Request) {}
+
+    /// Variables have been interpolated into the request.
+    fn request_resolved(&mut self, _request: &ResolvedRequest) {}
+
+    /// The `HttpClient` returned a response.
+    fn response_received(&mut self, _response: &HttpResponse, _duration: Duration) {}
+
+    /// A variable was extracted (or failed to be extracted) from the response.
+    fn extraction_performed(&mut self, _extraction: &Extraction) {}
+
+    /// An assertion was checked against the response.
+    fn assertion_evaluated(&mut self, _result: &AssertionResult) {}
+
+    /// The response was written to the file at `path`.
+    fn response_saved(&mut self, _path: &Path) {}
+
+    /// A request was not sent.
+    fn request_skipped(&mut self, _index: usize, _request: &Request, _reason: SkipReason) {}
+
+    /// Request `index` failed with `error`, and `ExecuteOptions::keep_going`
+    /// carries on with the next requests.
+    fn request_failed(&mut self, _index: usize, _request: &Request, _error: &ReqxError) {}
+
+    /// A started request is complete, whether or not it was sent.
+    fn request_finished(&mut self, _report: &RequestReport) {}
+
+    /// Every selected request has run.
+    fn run_finished(&mut self, _report: &ExecutionReport) {}
+}
+
+/// A `Reporter` that ignores every event.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {}
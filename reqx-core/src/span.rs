@@ -0,0 +1,80 @@
+//! Source positions.
+
+use std::ops::Range;
+
+/// A region of the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Byte offset of the first character.
+    pub start: usize,
+    /// Byte offset one past the last character.
+    pub end: usize,
+    /// 1-based line of `start`.
+    pub line: usize,
+    /// 1-based column (in characters) of `start`.
+    pub column: usize,
+}
+
+impl Span {
+    /// The span of `text[range]`, where `text` is the source text covered by `self`.
+    pub fn subspan(&self, text: &str, range: Range<usize>) -> Span {
+        let before = &text[..range.start];
+        let (line, column) = match before.rfind('\n') {
+            Some(nl) => (
+                self.line + before.matches('\n').count(),
+                before[nl + 1..].chars().count() + 1,
+            ),
+            None => (self.line, self.column + before.chars().count()),
+        };
+        Span {
+            start: self.start + range.start,
+            end: self.start + range.end,
+            line,
+            column,
+        }
+    }
+
+    /// The smallest span covering both `self` and `other`.
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end.max(self.end),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subspan_same_line() {
+        let span = Span {
+            start: 10,
+            end: 22,
+            line: 3,
+            column: 5,
+        };
+        let sub = span.subspan("GET {{host}}", 4..12);
+        assert_eq!(sub.start, 14);
+        assert_eq!(sub.end, 22);
+        assert_eq!(sub.line, 3);
+        assert_eq!(sub.column, 9);
+    }
+
+    #[test]
+    fn test_subspan_next_line() {
+        let span = Span {
+            start: 0,
+            end: 20,
+            line: 7,
+            column: 1,
+        };
+        let sub = span.subspan("{\n  \"a\": {{x}}\n}", 9..14);
+        assert_eq!(sub.line, 8);
+        assert_eq!(sub.column, 8);
+        assert_eq!(sub.start, 9);
+    }
+}
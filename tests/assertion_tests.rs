@@ -0,0 +1,91 @@
+mod common;
+
+use common::CannedClient;
+use reqx_core::interpreter::{ExecuteOptions, execute};
+use reqx_core::lexer::tokenize;
+use reqx_core::parser::parse;
+use reqx_core::reporter::SilentReporter;
+
+/// Answers with a created user.
+fn created() -> CannedClient {
+    CannedClient::new(
+        201,
+        &[("Content-Type", "application/json")],
+        r#"{ "user": { "id": 7, "role": "admin" } }"#,
+    )
+}
+
+#[test]
+fn test_assertions_pass_and_fail() {
+    let input = r#"
+@role = admin
+
+POST https://api.com/users
+Content-Type: application/json
+
+{ "name": "test" }
+
+@uid = user.id
+? status == 201
+? header content-type contains json
+? body.user.id == {{uid}}
+? body.user.role == {{role}}
+? duration < 10s
+
+###
+
+GET https://api.com/users/{{uid}}
+
+? status == 200
+? body.user.email exists
+"#;
+    let tokens = tokenize(input).unwrap();
+    let file = parse(tokens).unwrap();
+
+    let report = execute(
+        &created(),
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+
+    let first = &report.requests[0];
+    assert_eq!(first.assertions.len(), 5);
+    assert!(first.passed(), "{:?}", first.assertions);
+
+    let second = &report.requests[1];
+    assert!(!second.passed());
+    assert!(!report.passed());
+
+    let failures: Vec<(usize, String)> = report
+        .failures()
+        .map(|(i, a)| (i, a.message.clone().unwrap()))
+        .collect();
+    assert_eq!(
+        failures,
+        vec![
+            (1, "expected status == 200, got 201".to_string()),
+            (1, "body.user.email not found".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_assertions_skipped_on_dry_run() {
+    let input = "GET https://api.com/health\n? status == 200";
+    let file = parse(tokenize(input).unwrap()).unwrap();
+
+    let report = execute(
+        &created(),
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            dry_run: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(report.requests[0].assertions.is_empty());
+    assert!(report.passed());
+}
@@ -0,0 +1,119 @@
+//! Helpers shared by the test files.
+
+// Each test file uses its own subset of these.
+#![allow(dead_code)]
+
+use reqx_core::ast::HttpMethod;
+use reqx_core::client::{HttpClient, HttpResponse};
+use std::sync::Mutex;
+
+/// A request as `CannedClient` received it.
+#[derive(Debug, Clone)]
+pub struct Sent {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Sent {
+    /// The value of header `name`, if it was sent.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// An `HttpClient` that answers with canned responses and records every
+/// request it receives. A request whose URL contains the text of a `route`
+/// or `fail` gets that answer, the first one added winning; any other gets
+/// the default response.
+pub struct CannedClient {
+    routes: Vec<(String, Result<HttpResponse, String>)>,
+    default: HttpResponse,
+    sent: Mutex<Vec<Sent>>,
+}
+
+impl CannedClient {
+    /// Answer every request with `status`, `headers` and `body`.
+    pub fn new(status: u16, headers: &[(&str, &str)], body: &str) -> Self {
+        Self {
+            routes: Vec::new(),
+            default: response(status, headers, body),
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer every request with `200` and an empty JSON object.
+    pub fn ok() -> Self {
+        Self::new(200, &[], "{}")
+    }
+
+    /// Answer requests whose URL contains `url_part` with another response.
+    pub fn route(
+        mut self,
+        url_part: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> Self {
+        let answer = Ok(response(status, headers, body));
+        self.routes.push((url_part.to_string(), answer));
+        self
+    }
+
+    /// Fail requests whose URL contains `url_part` with transport error `error`.
+    pub fn fail(mut self, url_part: &str, error: &str) -> Self {
+        self.routes
+            .push((url_part.to_string(), Err(error.to_string())));
+        self
+    }
+
+    /// The requests received so far, in order.
+    pub fn sent(&self) -> Vec<Sent> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// The last request received, if any.
+    pub fn last(&self) -> Option<Sent> {
+        self.sent.lock().unwrap().last().cloned()
+    }
+}
+
+impl HttpClient for CannedClient {
+    fn execute(
+        &self,
+        method: &HttpMethod,
+        url: &str,
+        headers: &[(String, String)],
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, String> {
+        self.sent.lock().unwrap().push(Sent {
+            method: method.clone(),
+            url: url.to_string(),
+            headers: headers.to_vec(),
+            body: body.map(|b| String::from_utf8_lossy(b).into_owned()),
+        });
+        self.routes
+            .iter()
+            .find(|(part, _)| url.contains(part.as_str()))
+            .map(|(_, answer)| answer.clone())
+            .unwrap_or_else(|| Ok(self.default.clone()))
+    }
+}
+
+fn response(status: u16, headers: &[(&str, &str)], body: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        status_is_success: (200..300).contains(&status),
+        status_is_client_error: (400..500).contains(&status),
+        status_is_server_error: (500..600).contains(&status),
+        headers: headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        body: body.into(),
+    }
+}
@@ -1,8 +1,13 @@
+mod common;
+
+use common::CannedClient;
 use reqx_core::ast::HttpMethod;
-use reqx_core::client::HttpClient;
-use reqx_core::interpreter::execute;
+use reqx_core::error::ReqxError;
+use reqx_core::interpreter::{ExecuteOptions, execute};
 use reqx_core::lexer::tokenize;
 use reqx_core::parser::parse;
+use reqx_core::reporter::SilentReporter;
+use serde_json::{Value, json};
 
 #[test]
 fn test_variable_extraction() {
@@ -22,61 +27,314 @@ Authorization: Bearer {{token}}
     let tokens = tokenize(input).expect("Tokenization failed");
     let file = parse(tokens).expect("Parsing failed");
 
-    struct SequenceMockClient {
-        pub calls: std::sync::Mutex<Vec<(String, Vec<(String, String)>)>>,
-    }
-    impl HttpClient for SequenceMockClient {
-        fn execute(
-            &self,
-            _method: &HttpMethod,
-            url: &str,
-            headers: &[(String, String)],
-            _body: Option<&str>,
-        ) -> Result<reqx_core::client::HttpResponse, String> {
-            let mut calls = self.calls.lock().unwrap();
-            calls.push((url.to_string(), headers.to_vec()));
-
-            if url.contains("/login") {
-                Ok(reqx_core::client::HttpResponse {
-                    status: 200,
-                    status_is_success: true,
-                    status_is_client_error: false,
-                    status_is_server_error: false,
-                    headers: Vec::new(),
-                    body: r#"{ "token": "secret-123", "user": { "id": 42 } }"#.to_string(),
-                })
-            } else {
-                Ok(reqx_core::client::HttpResponse {
-                    status: 200,
-                    status_is_success: true,
-                    status_is_client_error: false,
-                    status_is_server_error: false,
-                    headers: Vec::new(),
-                    body: "OK".to_string(),
-                })
-            }
-        }
-    }
-
-    let client = SequenceMockClient {
-        calls: std::sync::Mutex::new(Vec::new()),
-    };
+    let client = CannedClient::new(200, &[], "OK").route(
+        "/login",
+        200,
+        &[],
+        r#"{ "token": "secret-123", "user": { "id": 42 } }"#,
+    );
 
     // Run both requests
-    execute(&client, &file, false, false, None, None).expect("Execution failed");
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .expect("Execution failed");
 
-    let calls = client.calls.lock().unwrap();
-    assert_eq!(calls.len(), 2);
+    let sent = client.sent();
+    assert_eq!(sent.len(), 2);
 
     // First call: login
-    assert_eq!(calls[0].0, "https://api.com/login");
+    assert_eq!(sent[0].url, "https://api.com/login");
 
     // Second call: user info with extracted variables
-    assert_eq!(calls[1].0, "https://api.com/user/42");
-    let auth = calls[1]
-        .1
+    assert_eq!(sent[1].url, "https://api.com/user/42");
+    assert_eq!(sent[1].header("Authorization"), Some("Bearer secret-123"));
+
+    // The report exposes the responses and the extracted values
+    assert_eq!(report.total, 2);
+    assert_eq!(report.requests.len(), 2);
+    let login = &report.requests[0];
+    assert_eq!(login.response.as_ref().map(|r| r.status), Some(200));
+    assert!(login.duration.is_some());
+    assert_eq!(login.extractions.len(), 2);
+    assert_eq!(login.extractions[1].name, "uid");
+    assert_eq!(login.extractions[1].value, Some(json!(42)));
+    assert_eq!(report.variables.get("token"), Some(&json!("secret-123")));
+}
+
+#[test]
+fn test_jsonpath_extraction() {
+    let input = r#"
+GET https://api.com/users
+
+@first = users.0.id
+@admins = $.users[?@.role == 'admin'].id
+@names = $.users[*].name
+@last = $.users[-1].name
+@missing? = $.users[5].id
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+
+    let client = CannedClient::new(
+        200,
+        &[],
+        r#"{"users": [
+            {"id": 1, "name": "ada", "role": "admin"},
+            {"id": 2, "name": "bob", "role": "user"},
+            {"id": 3, "name": "eve", "role": "admin"}
+        ]}"#,
+    );
+
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+    let values: Vec<(&str, Option<Value>)> = report.requests[0]
+        .extractions
         .iter()
-        .find(|(k, _)| k == "Authorization")
-        .map(|(_, v)| v.as_str());
-    assert_eq!(auth, Some("Bearer secret-123"));
+        .map(|e| (e.name.as_str(), e.value.clone()))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            ("first", Some(json!(1))),
+            ("admins", Some(json!([1, 3]))),
+            ("names", Some(json!(["ada", "bob", "eve"]))),
+            ("last", Some(json!("eve"))),
+            ("missing", None),
+        ]
+    );
+}
+
+#[test]
+fn test_typed_values_in_json_body() {
+    let client = CannedClient::new(
+        200,
+        &[],
+        r#"{"ids": [1, 2], "lead": "Ada \"A\" L", "active": true, "label": "true", "code": "42"}"#,
+    );
+
+    let input = r#"
+@limit = 10
+
+GET https://api.com/team
+
+@ids = $.ids
+@lead = lead
+@active = active
+@label = label
+@code = code
+
+###
+
+POST https://api.com/copy
+Content-Type: application/json
+
+{"ids": {{ids}}, "lead": {{lead}}, "note": "led by {{lead}}", "active": {{active}}, "label": {{label}}, "code": {{code}}, "limit": {{limit}}}
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(report.variables["ids"], json!([1, 2]));
+    assert_eq!(report.variables["active"], json!(true));
+    let body = report.requests[1].request.body_text().unwrap();
+    let sent: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        sent,
+        json!({
+            "ids": [1, 2],
+            "lead": "Ada \"A\" L",
+            "note": "led by Ada \"A\" L",
+            "active": true,
+            // Extracted strings stay strings, even if they look like JSON.
+            "label": "true",
+            "code": "42",
+            "limit": 10
+        })
+    );
+}
+
+#[test]
+fn test_failed_extraction_is_an_error() {
+    let client = CannedClient::new(
+        302,
+        &[("Location", "/home"), ("Set-Cookie", "sid=abc; HttpOnly")],
+        "<html>moved</html>",
+    );
+
+    let input = "POST https://api.com/login\n\n@loc = header Location\n@sid = cookie sid\n@code = status\n@user = user.id";
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let err = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap_err();
+    match err {
+        ReqxError::Extraction {
+            ref name,
+            ref message,
+            ref location,
+        } => {
+            assert_eq!(name, "user");
+            assert_eq!(message, "the response body is not valid JSON");
+            assert_eq!(location.span.line, 6);
+        }
+        ref other => panic!("expected an extraction error, got {:?}", other),
+    }
+    assert!(err.help().unwrap().contains("@user? = ..."));
+
+    let optional = input.replace("@user =", "@user? =");
+    let file = parse(tokenize(&optional).unwrap()).unwrap();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+    let get = |name: &str| report.variables.get(name).and_then(Value::as_str);
+    assert_eq!(get("loc"), Some("/home"));
+    assert_eq!(get("sid"), Some("abc"));
+    assert_eq!(report.variables["code"], 302);
+    assert_eq!(get("user"), None);
+}
+
+#[test]
+fn test_xml_and_html_extraction() {
+    let client = CannedClient::new(
+        200,
+        &[("Content-Type", "text/html")],
+        r#"<!DOCTYPE html><h1 class="title">Ada's profile</h1>
+        <a class="edit" href="/users/42/edit">Edit</a>"#,
+    )
+    .route(
+        "/soap",
+        200,
+        &[("Content-Type", "text/xml; charset=utf-8")],
+        r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+          <soap:Body><GetUserResponse xmlns="urn:users">
+            <user id="42"><name>Ada</name></user>
+          </GetUserResponse></soap:Body>
+        </soap:Envelope>"#,
+    );
+
+    let input = r#"
+POST https://api.com/soap
+
+@id = xpath //user/@id
+@name = //user/name
+
+###
+
+GET https://api.com/users/{{id}}
+
+@title = css h1.title
+@edit = css a.edit @href
+@heading = xpath //h1
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(report.requests[1].request.url, "https://api.com/users/42");
+    let get = |name: &str| report.variables.get(name).and_then(Value::as_str);
+    assert_eq!(get("name"), Some("Ada"));
+    assert_eq!(get("title"), Some("Ada's profile"));
+    assert_eq!(get("edit"), Some("/users/42/edit"));
+    assert_eq!(get("heading"), Some("Ada's profile"));
+
+    // A JSON path on an XML body points at `xpath`.
+    let file = parse(tokenize("POST https://api.com/soap\n\n@id = user.id").unwrap()).unwrap();
+    let err = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap_err();
+    assert!(err.message().contains("the response body is XML, not JSON"));
+}
+
+#[test]
+fn test_graphql_request() {
+    let input = r#"
+@id = 42
+
+GRAPHQL https://api.com/graphql
+
+query User($id: ID!) {
+  user(id: $id) { name }
+}
+
+{ "id": {{id}} }
+
+@name = user.name
+@first = $.data.user.name
+
+###
+
+POST https://api.com/graphql?broken
+X-Request-Type: GraphQL
+
+{ broken }
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+
+    let client = CannedClient::new(200, &[], r#"{"data": {"user": {"name": "Ada"}}}"#).route(
+        "broken",
+        200,
+        &[],
+        r#"{"data": null, "errors": [{"message": "Cannot query field `broken`"}]}"#,
+    );
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+
+    let sent = client.sent();
+    assert_eq!(sent[0].method, HttpMethod::Post);
+    assert_eq!(sent[0].header("Content-Type"), Some("application/json"));
+    let body: Value = serde_json::from_str(sent[0].body.as_deref().unwrap()).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "query": "query User($id: ID!) {\n  user(id: $id) { name }\n}",
+            "variables": {"id": 42}
+        })
+    );
+    assert_eq!(report.variables["name"], "Ada");
+    assert_eq!(report.variables["first"], "Ada");
+    assert!(report.requests[0].passed());
+
+    // The header marks the request and is not sent.
+    assert_eq!(sent[1].header("X-Request-Type"), None);
+    assert_eq!(sent[1].body.as_deref(), Some(r#"{"query":"{ broken }"}"#));
+    let failed = &report.requests[1].assertions[0];
+    assert!(!failed.passed);
+    assert_eq!(
+        failed.message.as_deref(),
+        Some("the response has 1 GraphQL error(s): Cannot query field `broken`")
+    );
+    assert!(!report.passed());
 }
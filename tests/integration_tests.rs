@@ -1,47 +1,12 @@
+mod common;
+
+use common::CannedClient;
 use reqx_core::ast::HttpMethod;
-use reqx_core::client::HttpClient;
-use reqx_core::interpreter::execute;
+use reqx_core::error::ReqxError;
+use reqx_core::interpreter::{ExecuteOptions, execute};
 use reqx_core::lexer::tokenize;
 use reqx_core::parser::parse;
-
-#[derive(Debug)]
-struct CapturedRequest {
-    pub method: HttpMethod,
-    pub url: String,
-    pub headers: Vec<(String, String)>,
-    pub body: Option<String>,
-}
-
-struct MockClient {
-    pub last_request: std::sync::Mutex<Option<CapturedRequest>>,
-}
-
-impl HttpClient for MockClient {
-    fn execute(
-        &self,
-        method: &HttpMethod,
-        url: &str,
-        headers: &[(String, String)],
-        body: Option<&str>,
-    ) -> Result<reqx_core::client::HttpResponse, String> {
-        let mut last = self.last_request.lock().unwrap();
-        *last = Some(CapturedRequest {
-            method: method.clone(),
-            url: url.to_string(),
-            headers: headers.to_owned(),
-            body: body.map(|b| b.to_string()),
-        });
-
-        Ok(reqx_core::client::HttpResponse {
-            status: 200,
-            status_is_success: true,
-            status_is_client_error: false,
-            status_is_server_error: false,
-            headers: Vec::new(),
-            body: "{}".to_string(),
-        })
-    }
-}
+use reqx_core::reporter::SilentReporter;
 
 #[test]
 fn test_exhaustive_integration_success() {
@@ -72,14 +37,21 @@ Accept: application/json
 
     let tokens = tokenize(input).expect("Tokenization failed");
     let file = parse(tokens).expect("Parsing failed");
-    let client = MockClient {
-        last_request: std::sync::Mutex::new(None),
-    };
+    let client = CannedClient::ok();
 
     // Run first request
-    execute(&client, &file, false, false, Some(1), None).expect("Execution failed");
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            request_index: Some(1),
+            ..Default::default()
+        },
+    )
+    .expect("Execution failed");
     {
-        let last = client.last_request.lock().unwrap().take().unwrap();
+        let last = client.last().unwrap();
         assert_eq!(last.method, HttpMethod::Get);
         assert_eq!(last.url, "https://api.example.com/v1/users");
         assert!(
@@ -90,18 +62,36 @@ Accept: application/json
     }
 
     // Run second request (implicit POST)
-    execute(&client, &file, false, false, Some(2), None).expect("Execution failed");
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            request_index: Some(2),
+            ..Default::default()
+        },
+    )
+    .expect("Execution failed");
     {
-        let last = client.last_request.lock().unwrap().take().unwrap();
+        let last = client.last().unwrap();
         assert_eq!(last.method, HttpMethod::Post);
         assert_eq!(last.url, "https://api.example.com/v1/data");
         assert!(last.body.unwrap().contains("secret123"));
     }
 
     // Run third request (localhost shorthand)
-    execute(&client, &file, false, false, Some(3), None).expect("Execution failed");
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            request_index: Some(3),
+            ..Default::default()
+        },
+    )
+    .expect("Execution failed");
     {
-        let last = client.last_request.lock().unwrap().take().unwrap();
+        let last = client.last().unwrap();
         assert_eq!(last.url, "http://localhost:8080/status");
     }
 }
@@ -112,20 +102,43 @@ fn test_exhaustive_integration_errors() {
     let input_err1 = "GET https://{{missing_var}}.com";
     let tokens = tokenize(input_err1).unwrap();
     let file = parse(tokens).unwrap();
-    let client = MockClient {
-        last_request: std::sync::Mutex::new(None),
-    };
-    let res = execute(&client, &file, false, false, None, None);
+    let client = CannedClient::ok();
+    let res = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    );
     assert!(res.is_err());
-    assert!(res.unwrap_err().contains("Undefined variable"));
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("Undefined variable"));
+    match err {
+        ReqxError::UndefinedVariable { name, location, .. } => {
+            assert_eq!(name, "missing_var");
+            assert_eq!(location.span.line, 1);
+            assert_eq!(location.span.column, 13);
+            assert_eq!(
+                &input_err1[location.span.start..location.span.end],
+                "{{missing_var}}"
+            );
+        }
+        other => panic!("expected an undefined variable error, got {:?}", other),
+    }
 
     // 2. Unclosed variable interpolation
     let input_err2 = "GET https://example.com/{{unclosed";
     let tokens = tokenize(input_err2).unwrap();
     let file = parse(tokens).unwrap();
-    let res = execute(&client, &file, false, false, None, None);
+    let res = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    );
     assert!(res.is_err());
-    assert!(res.unwrap_err().contains("Unclosed variable interpolation"));
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("Unclosed variable interpolation"));
+    assert!(matches!(err, ReqxError::UnclosedInterpolation { .. }));
 }
 
 #[test]
@@ -140,9 +153,7 @@ GET https://api.com/{{count}}
 "#;
     let tokens = tokenize(input).unwrap();
     let file = parse(tokens).unwrap();
-    let client = MockClient {
-        last_request: std::sync::Mutex::new(None),
-    };
+    let client = CannedClient::ok();
 
     // The interpreter currently rebuilds the variable map once at the start of `execute`.
     // Wait, let's check the code for `interpreter.rs`.
@@ -150,9 +161,18 @@ GET https://api.com/{{count}}
     // My parser puts ALL variables into `file.variables`.
     // If a variable is redefined, the last one wins (HashMap `insert`).
 
-    execute(&client, &file, false, false, Some(1), None).unwrap();
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            request_index: Some(1),
+            ..Default::default()
+        },
+    )
+    .unwrap();
     {
-        let last = client.last_request.lock().unwrap().take().unwrap();
+        let last = client.last().unwrap();
         // Since both @count = 1 and @count = 2 are in `file.variables`,
         // the HashMap will have count = 2 at the end of the loop.
         // This might be a bug or intended behavior (global scope).
@@ -160,3 +180,732 @@ GET https://api.com/{{count}}
         assert_eq!(last.url, "https://api.com/2");
     }
 }
+
+#[test]
+fn test_select_requests_by_name() {
+    let input = r#"
+# @name login
+POST https://api.com/login
+
+###
+
+# @name user-create
+POST https://api.com/users
+
+###
+
+# @name user-delete
+DELETE https://api.com/users/1
+
+###
+
+GET https://api.com/health
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let run = |patterns: &[&str]| {
+        execute(
+            &client,
+            &file,
+            &mut SilentReporter,
+            &ExecuteOptions {
+                dry_run: true,
+                name_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+                ..Default::default()
+            },
+        )
+        .map(|report| {
+            report
+                .requests
+                .iter()
+                .map(|r| r.name.clone().unwrap_or_default())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(
+        run(&["user-*"]).unwrap(),
+        vec!["user-create", "user-delete"]
+    );
+    assert_eq!(
+        run(&["user-delete", "login"]).unwrap(),
+        vec!["login", "user-delete"]
+    );
+    assert_eq!(run(&[]).unwrap().len(), 4);
+
+    match run(&["logn"]).unwrap_err() {
+        ReqxError::InvalidSelection { message } => {
+            assert_eq!(message, "No request is named `logn`. Did you mean `login`?")
+        }
+        other => panic!("expected an invalid selection error, got {:?}", other),
+    }
+    assert!(run(&["admin-*"]).is_err());
+}
+
+#[test]
+fn test_seeded_builtin_variables() {
+    let input = r#"
+POST https://api.com/users/{{$uuid}}
+X-Request-Id: {{$randomInt 1 1000000}}
+
+{"name": "user-{{$randomString 8}}", "created": "{{$datetime "%Y" -1d}}"}
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let run = |seed| {
+        let report = execute(
+            &client,
+            &file,
+            &mut SilentReporter,
+            &ExecuteOptions {
+                dry_run: true,
+                seed: Some(seed),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let request = report.requests[0].request.clone();
+        let body = request.body_text().unwrap().to_string();
+        (request.url, request.headers, body)
+    };
+
+    let first = run(42);
+    assert_eq!(first, run(42));
+    assert_ne!(first, run(43));
+    assert!(!first.0.contains("{{"));
+    assert!(!first.2.contains("{{"));
+}
+
+#[test]
+fn test_body_from_file() {
+    let dir = std::env::temp_dir().join(format!("reqx-body-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("fixtures")).unwrap();
+    std::fs::write(dir.join("fixtures/user.json"), r#"{"name": "{{name}}"}"#).unwrap();
+    std::fs::write(dir.join("avatar.png"), [0x89, b'P', b'N', b'G', 0xff, 0x00]).unwrap();
+
+    let input = r#"
+@name = Ada
+
+POST https://api.com/raw
+
+< ./fixtures/user.json
+
+###
+
+POST https://api.com/template
+Content-Type: application/json
+
+<@ ./fixtures/user.json
+
+###
+
+PUT https://api.com/avatar
+Content-Type: image/png
+
+< avatar.png
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        report.requests[0].request.body_text(),
+        Some(r#"{"name": "{{name}}"}"#)
+    );
+    assert_eq!(
+        report.requests[1].request.body_text(),
+        Some(r#"{"name": "Ada"}"#)
+    );
+    assert_eq!(
+        report.requests[2].request.body.as_deref(),
+        Some(&[0x89, b'P', b'N', b'G', 0xff, 0x00][..])
+    );
+
+    let file = parse(tokenize("POST https://api.com\n\n< ./missing.json").unwrap()).unwrap();
+    let err = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ReqxError::File { .. }));
+    assert!(err.message().starts_with("cannot read `./missing.json`"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_multipart_body() {
+    let dir = std::env::temp_dir().join(format!("reqx-multipart-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("fixtures")).unwrap();
+    std::fs::write(dir.join("fixtures/beach.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+    let input = r#"
+@title = Holiday
+
+POST https://api.com/photos
+
+--- title
+{{title}}
+
+--- photo
+Content-Type: image/png
+
+< ./fixtures/beach.png
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            seed: Some(1),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let request = &report.requests[0].request;
+    let (_, content_type) = request
+        .headers
+        .iter()
+        .find(|(k, _)| k == "Content-Type")
+        .unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    assert!(boundary.starts_with("reqx-"));
+
+    let mut expected = format!(
+        "--{b}\r\n\
+         Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+         Holiday\r\n\
+         --{b}\r\n\
+         Content-Disposition: form-data; name=\"photo\"; filename=\"beach.png\"\r\n\
+         Content-Type: image/png\r\n\r\n",
+        b = boundary
+    )
+    .into_bytes();
+    expected.extend_from_slice(&[0x89, b'P', b'N', b'G']);
+    expected.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    assert_eq!(request.body.as_deref(), Some(&expected[..]));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_query_and_form() {
+    let input = r#"
+@term = rust & go
+
+POST https://api.com/search?v=2
+    ?q={{term}}
+    &page=1
+
+&name=Jane Doe
+&note=50% off
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let report = execute(&client, &file, &mut SilentReporter, &Default::default()).unwrap();
+    let request = &report.requests[0].request;
+    assert_eq!(
+        request.url,
+        "https://api.com/search?v=2&q=rust%20%26%20go&page=1"
+    );
+    assert!(request.headers.contains(&(
+        "Content-Type".to_string(),
+        "application/x-www-form-urlencoded".to_string()
+    )));
+    assert_eq!(
+        request.body_text(),
+        Some("name=Jane%20Doe&note=50%25%20off")
+    );
+}
+
+#[test]
+fn test_env_variables() {
+    let file = parse(
+        tokenize("GET https://api.com/{{$env.REQX_IT_TOKEN}}\nX-Missing: {{$env.REQX_IT_MISSING}}")
+            .unwrap(),
+    )
+    .unwrap();
+    let client = CannedClient::ok();
+    let run = |env: &[(&str, &str)]| {
+        execute(
+            &client,
+            &file,
+            &mut SilentReporter,
+            &ExecuteOptions {
+                dry_run: true,
+                env: env
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                ..Default::default()
+            },
+        )
+    };
+
+    let report = run(&[("REQX_IT_TOKEN", "t0k"), ("REQX_IT_MISSING", "x")]).unwrap();
+    assert_eq!(report.requests[0].request.url, "https://api.com/t0k");
+
+    match run(&[("REQX_IT_TOKEN", "t0k")]).unwrap_err() {
+        ReqxError::Builtin { message, .. } => {
+            assert_eq!(message, "environment variable `REQX_IT_MISSING` is not set")
+        }
+        other => panic!("expected a built-in variable error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_environment_layers() {
+    use reqx_core::interpreter::VariableLayer;
+    use reqx_core::report::VariableOrigin;
+    use reqx_core::reporter::Reporter;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct OriginReporter {
+        origins: HashMap<String, VariableOrigin>,
+    }
+    impl Reporter for OriginReporter {
+        fn run_started(
+            &mut self,
+            _variables: &HashMap<String, serde_json::Value>,
+            origins: &HashMap<String, VariableOrigin>,
+        ) {
+            self.origins = origins.clone();
+        }
+    }
+
+    let layer = |name: &str, values: &[(&str, &str)]| VariableLayer {
+        name: name.to_string(),
+        values: values
+            .iter()
+            .map(|(k, v)| (k.to_string(), serde_json::Value::from(*v)))
+            .collect(),
+        untyped: false,
+    };
+    let file =
+        parse(tokenize("@version = v3\nGET {{base_url}}/{{version}}/{{tenant}}").unwrap()).unwrap();
+    let client = CannedClient::ok();
+    let mut reporter = OriginReporter::default();
+    let report = execute(
+        &client,
+        &file,
+        &mut reporter,
+        &ExecuteOptions {
+            dry_run: true,
+            environment: vec![
+                layer(
+                    "$shared",
+                    &[
+                        ("base_url", "http://localhost"),
+                        ("version", "v1"),
+                        ("tenant", "acme"),
+                    ],
+                ),
+                layer(
+                    "staging",
+                    &[("base_url", "https://staging.api.com"), ("version", "v2")],
+                ),
+            ],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        report.requests[0].request.url,
+        "https://staging.api.com/v3/acme"
+    );
+    assert_eq!(
+        reporter.origins["base_url"],
+        VariableOrigin::Environment("staging".to_string())
+    );
+    assert_eq!(
+        reporter.origins["tenant"],
+        VariableOrigin::Environment("$shared".to_string())
+    );
+    assert_eq!(reporter.origins["version"], VariableOrigin::File);
+
+    let report = execute(
+        &client,
+        &file,
+        &mut reporter,
+        &ExecuteOptions {
+            dry_run: true,
+            environment: vec![layer("$shared", &[("base_url", "http://localhost")])],
+            overrides: vec![
+                layer("vars.json", &[("version", "v4"), ("tenant", "globex")]),
+                layer("--var", &[("tenant", "initech")]),
+            ],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        report.requests[0].request.url,
+        "http://localhost/v4/initech"
+    );
+    assert_eq!(
+        reporter.origins["version"],
+        VariableOrigin::Override("vars.json".to_string())
+    );
+    assert_eq!(
+        reporter.origins["tenant"],
+        VariableOrigin::Override("--var".to_string())
+    );
+}
+
+#[test]
+fn test_var_overrides_are_untyped() {
+    use reqx_core::interpreter::VariableLayer;
+
+    let layer = |name: &str, values: &[(&str, &str)], untyped| VariableLayer {
+        name: name.to_string(),
+        values: values
+            .iter()
+            .map(|(k, v)| (k.to_string(), serde_json::Value::from(*v)))
+            .collect(),
+        untyped,
+    };
+    let file = parse(
+        tokenize(
+            "@id = 1\nPOST :3000\nContent-Type: application/json\n\n\
+             {\"id\": {{id}}, \"name\": {{name}}, \"label\": \"{{id}}\", \"code\": {{code}}}",
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let client = CannedClient::ok();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            dry_run: true,
+            overrides: vec![
+                layer("vars.json", &[("code", "7")], false),
+                layer("--var", &[("id", "42"), ("name", "Jane")], true),
+            ],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        report.requests[0].request.body_text(),
+        Some(r#"{"id": 42, "name": "Jane", "label": "42", "code": "7"}"#)
+    );
+}
+
+#[test]
+fn test_dry_run_report() {
+    let input = r#"
+@host = api.example.com
+
+# Create
+POST https://{{host}}/items
+Content-Type: application/json
+
+{"name": "{{host}}"}
+"#;
+    let tokens = tokenize(input).unwrap();
+    let file = parse(tokens).unwrap();
+    let client = CannedClient::ok();
+
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            dry_run: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(client.last().is_none());
+    assert_eq!(report.requests.len(), 1);
+
+    let req = &report.requests[0];
+    assert_eq!(req.comment.as_deref(), Some("Create"));
+    assert_eq!(req.request.method, HttpMethod::Post);
+    assert_eq!(req.request.url, "https://api.example.com/items");
+    assert_eq!(
+        req.request.body_text(),
+        Some(r#"{"name": "api.example.com"}"#)
+    );
+    assert!(req.response.is_none());
+    assert!(req.duration.is_none());
+}
+
+#[test]
+fn test_reporter_events() {
+    use reqx_core::ast::Request;
+    use reqx_core::client::HttpResponse;
+    use reqx_core::report::{
+        ExecutionReport, Extraction, RequestReport, ResolvedRequest, VariableOrigin,
+    };
+    use reqx_core::reporter::{Reporter, SkipReason};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct RecordingReporter {
+        events: Vec<String>,
+    }
+
+    impl Reporter for RecordingReporter {
+        fn run_started(
+            &mut self,
+            variables: &HashMap<String, serde_json::Value>,
+            _origins: &HashMap<String, VariableOrigin>,
+        ) {
+            self.events
+                .push(format!("run_started({})", variables.len()));
+        }
+        fn request_started(&mut self, index: usize, total: usize, _request: &Request) {
+            self.events
+                .push(format!("request_started({}/{})", index + 1, total));
+        }
+        fn request_resolved(&mut self, request: &ResolvedRequest) {
+            self.events.push(format!(
+                "request_resolved({} {})",
+                request.method, request.url
+            ));
+        }
+        fn response_received(&mut self, response: &HttpResponse, _duration: Duration) {
+            self.events
+                .push(format!("response_received({})", response.status));
+        }
+        fn extraction_performed(&mut self, extraction: &Extraction) {
+            self.events
+                .push(format!("extraction_performed({})", extraction.name));
+        }
+        fn request_skipped(&mut self, index: usize, _request: &Request, reason: SkipReason) {
+            self.events
+                .push(format!("request_skipped({}, {:?})", index + 1, reason));
+        }
+        fn request_finished(&mut self, report: &RequestReport) {
+            self.events
+                .push(format!("request_finished({})", report.index + 1));
+        }
+        fn run_finished(&mut self, report: &ExecutionReport) {
+            self.events
+                .push(format!("run_finished({})", report.requests.len()));
+        }
+    }
+
+    let input = r#"
+@host = api.example.com
+
+GET https://{{host}}/a
+
+@empty? = missing
+
+###
+
+DELETE https://{{host}}/b
+"#;
+    let tokens = tokenize(input).unwrap();
+    let file = parse(tokens).unwrap();
+    let client = CannedClient::ok();
+
+    let mut reporter = RecordingReporter::default();
+    execute(
+        &client,
+        &file,
+        &mut reporter,
+        &ExecuteOptions {
+            method_filter: Some("GET".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        reporter.events,
+        vec![
+            "run_started(1)",
+            "request_started(1/2)",
+            "request_resolved(GET https://api.example.com/a)",
+            "response_received(200)",
+            "extraction_performed(empty)",
+            "request_finished(1)",
+            "request_skipped(2, NotSelected)",
+            "run_finished(1)",
+        ]
+    );
+
+    let mut reporter = RecordingReporter::default();
+    execute(
+        &client,
+        &file,
+        &mut reporter,
+        &ExecuteOptions {
+            dry_run: true,
+            request_index: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        reporter.events,
+        vec![
+            "run_started(1)",
+            "request_skipped(1, NotSelected)",
+            "request_started(2/2)",
+            "request_resolved(DELETE https://api.example.com/b)",
+            "request_skipped(2, DryRun)",
+            "request_finished(2)",
+            "run_finished(1)",
+        ]
+    );
+}
+
+#[test]
+fn test_save_responses() {
+    let json = [("Content-Type", "application/json")];
+    let client = CannedClient::new(200, &json, "{}")
+        .route("/users/1", 200, &json, "{\"id\": 1}\n")
+        .route("/users/2", 200, &json, "{\"id\": 2}\n");
+
+    let dir = std::env::temp_dir().join(format!("reqx-output-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("log.json"), "{\"id\": 0}\n").unwrap();
+
+    let input = r#"
+# @name first
+GET https://api.com/users/1
+@id = id
+> ./users/{{id}}.json
+
+###
+
+GET https://api.com/users/2
+>> ./log.json
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            output_dir: Some(dir.join("responses")),
+            output_headers: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+    assert_eq!(read("users/1.json"), "{\"id\": 1}\n");
+    assert_eq!(read("log.json"), "{\"id\": 0}\n{\"id\": 2}\n");
+    assert_eq!(read("responses/first.json"), "{\"id\": 1}\n");
+    assert_eq!(read("responses/request-2.json"), "{\"id\": 2}\n");
+    assert_eq!(
+        read("responses/first.headers"),
+        "HTTP 200\nContent-Type: application/json\n"
+    );
+    assert_eq!(report.requests[0].saved.len(), 3);
+    assert_eq!(report.requests[0].saved[2], dir.join("./users/1.json"));
+
+    // Nothing is written in a dry run.
+    std::fs::remove_dir_all(&dir).unwrap();
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            output_dir: Some(dir.join("responses")),
+            dry_run: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_multipart_keeps_seeded_values() {
+    let url_of_last = |input: &str| {
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        let client = CannedClient::ok();
+        let report = execute(
+            &client,
+            &file,
+            &mut SilentReporter,
+            &ExecuteOptions {
+                seed: Some(7),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        report.requests.last().unwrap().request.url.clone()
+    };
+    let with_multipart =
+        "POST https://api.com/upload\n\n--- title\nHoliday\n\n###\n\nGET https://api.com/{{$uuid}}";
+    let without = "GET https://api.com/status\n\n###\n\nGET https://api.com/{{$uuid}}";
+    assert_eq!(url_of_last(with_multipart), url_of_last(without));
+}
+
+#[test]
+fn test_keep_going_after_errors() {
+    let input = r#"
+GET https://api.com/first?token={{auth.response.status}}
+
+###
+
+GET https://api.com/ok
+
+###
+
+# @name auth
+GET https://down.api.com/login
+
+###
+
+GET https://down.api.com/other
+
+###
+
+GET https://api.com/last
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let options = ExecuteOptions {
+        keep_going: true,
+        ..Default::default()
+    };
+    let client = CannedClient::ok().fail("down", "connection refused");
+    let report = execute(&client, &file, &mut SilentReporter, &options).unwrap();
+
+    // `auth` fails when run on demand, then the first request fails without it.
+    let failed: Vec<usize> = report.errors.iter().map(|(i, _)| *i).collect();
+    assert_eq!(failed, vec![2, 0, 3]);
+    assert!(matches!(report.errors[0].1, ReqxError::Transport { .. }));
+    assert!(matches!(report.errors[1].1, ReqxError::Reference { .. }));
+    let ran: Vec<usize> = report.requests.iter().map(|r| r.index).collect();
+    assert_eq!(ran, vec![1, 4]);
+    assert!(!report.passed());
+
+    // Without it, the first error ends the run.
+    let options = ExecuteOptions::default();
+    let err = execute(&client, &file, &mut SilentReporter, &options).unwrap_err();
+    assert!(matches!(err, ReqxError::Transport { .. }));
+}
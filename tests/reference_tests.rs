@@ -0,0 +1,189 @@
+mod common;
+
+use common::CannedClient;
+use reqx_core::error::ReqxError;
+use reqx_core::interpreter::{ExecuteOptions, execute};
+use reqx_core::lexer::tokenize;
+use reqx_core::parser::parse;
+use reqx_core::reporter::SilentReporter;
+
+/// Answers `/login` with a token, and every request with a `Location` header.
+fn login_client() -> CannedClient {
+    let location = [("Location", "/users/7")];
+    CannedClient::new(200, &location, "{}").route(
+        "/login",
+        201,
+        &location,
+        r#"{"token": "abc", "user": {"id": 7}}"#,
+    )
+}
+
+const INPUT: &str = r#"
+# @name me
+GET https://api.com/me
+Authorization: Bearer {{login.response.body.$.token}}
+
+? status == {{login.response.status}}
+
+###
+
+# @name login
+POST https://api.com/login
+
+###
+
+GET https://api.com{{login.response.headers.Location}}?id={{login.response.body.user.id}}
+"#;
+
+#[test]
+fn test_references_run_requests_on_demand() {
+    let file = parse(tokenize(INPUT).unwrap()).unwrap();
+    let client = login_client();
+
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions::default(),
+    )
+    .unwrap();
+
+    // `login` runs before `me`, which needs it, and only once.
+    let sent = client.sent();
+    let urls: Vec<&str> = sent.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://api.com/login",
+            "https://api.com/me",
+            "https://api.com/users/7?id=7",
+        ]
+    );
+    assert_eq!(sent[1].header("Authorization"), Some("Bearer abc"));
+    let order: Vec<usize> = report.requests.iter().map(|r| r.index).collect();
+    assert_eq!(order, vec![1, 0, 2]);
+    assert!(!report.passed(), "`me` returns 200, not login's 201");
+}
+
+#[test]
+fn test_references_run_unselected_requests() {
+    let file = parse(tokenize(INPUT).unwrap()).unwrap();
+    let client = login_client();
+
+    execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            name_patterns: vec!["me".to_string()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(client.sent().len(), 2);
+}
+
+#[test]
+fn test_references_kept_on_dry_run() {
+    let file = parse(tokenize(INPUT).unwrap()).unwrap();
+    let report = execute(
+        &login_client(),
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            dry_run: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        report.requests[0].request.headers[0].1,
+        "Bearer {{login.response.body.$.token}}"
+    );
+}
+
+#[test]
+fn test_reference_errors() {
+    let run = |input: &str| {
+        let file = parse(tokenize(input).unwrap()).unwrap();
+        execute(
+            &login_client(),
+            &file,
+            &mut SilentReporter,
+            &ExecuteOptions::default(),
+        )
+        .unwrap_err()
+    };
+
+    let err = run(
+        "# @name a\nGET https://api.com/{{b.response.status}}\n###\n# @name b\nGET https://api.com/{{a.response.status}}",
+    );
+    assert!(matches!(err, ReqxError::Reference { .. }));
+    assert_eq!(err.message(), "circular request reference: a -> b -> a");
+    assert_eq!(err.location().unwrap().span.line, 5);
+
+    let err = run(
+        "# @name login\nPOST https://api.com/login\n###\nGET https://api.com/{{logn.response.status}}",
+    );
+    assert_eq!(err.message(), "no request is named `logn`");
+    assert_eq!(err.help().unwrap(), "did you mean `login`?");
+
+    let err = run(
+        "# @name login\nPOST https://api.com/login\n###\nGET https://api.com/{{login.response.body.$.missing}}",
+    );
+    assert!(err.message().contains("is not in the response body"));
+}
+
+#[test]
+fn test_references_in_templates() {
+    let dir = std::env::temp_dir().join(format!("reqx-template-ref-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("me.json"),
+        r#"{"token": "{{login.response.body.$.token}}"}"#,
+    )
+    .unwrap();
+
+    let input = r#"
+POST https://api.com/me
+Content-Type: application/json
+
+<@ ./me.json
+
+###
+
+POST https://api.com/photos
+
+--- meta
+<@ ./me.json
+
+###
+
+# @name login
+POST https://api.com/login
+"#;
+    let file = parse(tokenize(input).unwrap()).unwrap();
+    let client = login_client();
+    let report = execute(
+        &client,
+        &file,
+        &mut SilentReporter,
+        &ExecuteOptions {
+            base_dir: dir.clone(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(client.sent()[0].url, "https://api.com/login");
+    let me = report.requests.iter().find(|r| r.index == 0).unwrap();
+    assert_eq!(me.request.body_text(), Some(r#"{"token": "abc"}"#));
+    let photos = report.requests.iter().find(|r| r.index == 1).unwrap();
+    assert!(
+        photos
+            .request
+            .body_text()
+            .unwrap()
+            .contains(r#"{"token": "abc"}"#)
+    );
+}